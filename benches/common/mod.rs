@@ -0,0 +1,48 @@
+//! Minimal benchmark harness shared by the benches, which run without the
+//! unstable `test` crate.
+
+use std::time::Instant;
+
+/// OGN positions, a compressed position and a message
+pub const PACKETS: &[&[u8]] = &[
+    br"ICA3D17F2>APRS,qAS,dl4mea:/074849h4821.61N\01224.49E^322/103/A=003054 !W09! id213D17F2 -039fpm +0.0rot 2.5dB 3e -0.0kHz gps1x1",
+    br"FLRDDA5BA>APRS,qAS,LFMX:/165829h4415.41N/00600.03E'342/049/A=005524 id0ADDA5BA -454fpm -1.1rot 8.8dB 0e +51.2kHz gps4x5",
+    br"ICA3D17F2>APRS,qAS,dl4mea:@074849h/ABCD#$%^^{?C322/103/A=003054",
+    br"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :Hello World! This msg has a : colon {3a2B975",
+];
+
+const ITERATIONS: u32 = 200_000;
+
+// `std::hint::black_box` is not available on our minimum supported Rust version
+pub fn black_box<T>(x: T) -> T {
+    unsafe {
+        let ret = std::ptr::read_volatile(&x);
+        std::mem::forget(x);
+        ret
+    }
+}
+
+/// Runs `f` on all `items` repeatedly and prints the time per item
+pub fn bench<T, F: FnMut(&T)>(name: &str, items: &[T], mut f: F) {
+    // warm up
+    for item in items {
+        f(black_box(item));
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        for item in items {
+            f(black_box(item));
+        }
+    }
+    let elapsed = start.elapsed();
+
+    let per_packet = elapsed / (ITERATIONS * items.len() as u32);
+    let packets_per_second = ITERATIONS as f64 * items.len() as f64 / elapsed.as_secs_f64();
+    println!(
+        "{:<12} {:>8} ns/packet {:>12.0} packets/s",
+        name,
+        per_packet.as_nanos(),
+        packets_per_second
+    );
+}
@@ -0,0 +1,39 @@
+//! Compares encoding into a `Vec` with the allocation free
+//! `encode_to_slice` and `encoded_len`.
+//!
+//! Run with `cargo bench --bench encode`.
+
+extern crate aprs_parser;
+
+mod common;
+
+use common::{bench, black_box, PACKETS};
+
+fn main() {
+    let packets: Vec<_> = PACKETS
+        .iter()
+        .map(|p| aprs_parser::parse(p).unwrap())
+        .collect();
+
+    bench("vec", &packets, |p| {
+        let mut buf = Vec::new();
+        p.encode(&mut buf).unwrap();
+        black_box(buf);
+    });
+
+    let mut buf = Vec::with_capacity(256);
+    bench("reused vec", &packets, |p| {
+        buf.clear();
+        p.encode(&mut buf).unwrap();
+        black_box(&buf);
+    });
+
+    let mut buf = [0; 256];
+    bench("slice", &packets, |p| {
+        black_box(p.encode_to_slice(&mut buf).unwrap());
+    });
+
+    bench("encoded_len", &packets, |p| {
+        black_box(p.encoded_len().unwrap());
+    });
+}
@@ -0,0 +1,23 @@
+//! Compares parsing into owned and borrowed packets.
+//!
+//! Run with `cargo bench --bench parse`.
+
+extern crate aprs_parser;
+
+mod common;
+
+use common::{bench, black_box, PACKETS};
+
+fn main() {
+    bench("owned", PACKETS, |p| {
+        black_box(aprs_parser::parse(p).unwrap());
+    });
+
+    bench("borrowed", PACKETS, |p| {
+        black_box(aprs_parser::parse_ref(p).unwrap());
+    });
+
+    bench("borrowed+own", PACKETS, |p| {
+        black_box(aprs_parser::parse_ref(p).unwrap().into_owned());
+    });
+}
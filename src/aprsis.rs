@@ -0,0 +1,469 @@
+// APRS-IS client protocol
+//
+// After connecting, the server sends a banner comment, the client answers with
+// a login line and the server confirms it with a `logresp` comment. From then
+// on the server sends packets interleaved with keepalive comments.
+
+use std::convert::TryFrom;
+use std::io::{BufReader, Read, Write};
+
+use AprsLine;
+use AprsPacket;
+use AprsReader;
+use Callsign;
+use EncodeError;
+use LoginError;
+use ReadLine;
+
+/// Computes the APRS-IS passcode for a callsign. The SSID is ignored.
+pub fn passcode(callsign: &Callsign) -> u16 {
+    let call = callsign.call.to_ascii_uppercase();
+    let mut hash: u16 = 0x73e2;
+
+    for pair in call.as_bytes().chunks(2) {
+        hash ^= (pair[0] as u16) << 8;
+        if let Some(&b) = pair.get(1) {
+            hash ^= b as u16;
+        }
+    }
+
+    hash & 0x7fff
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Login {
+    pub callsign: Callsign,
+    /// `None` logs in as an unverified, receive-only client
+    pub passcode: Option<u16>,
+    pub software_name: String,
+    pub software_version: String,
+    pub filter: Option<String>,
+}
+
+impl Login {
+    pub fn new<N: Into<String>, V: Into<String>>(
+        callsign: Callsign,
+        software_name: N,
+        software_version: V,
+    ) -> Self {
+        Self {
+            callsign,
+            passcode: None,
+            software_name: software_name.into(),
+            software_version: software_version.into(),
+            filter: None,
+        }
+    }
+
+    pub fn passcode(mut self, passcode: u16) -> Self {
+        self.passcode = Some(passcode);
+        self
+    }
+
+    pub fn filter<T: Into<String>>(mut self, filter: T) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    pub fn encode<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
+        write!(buf, "user {} pass ", self.callsign)?;
+        match self.passcode {
+            Some(passcode) => write!(buf, "{}", passcode)?,
+            None => write!(buf, "-1")?,
+        }
+        write!(
+            buf,
+            " vers {} {}",
+            self.software_name, self.software_version
+        )?;
+        if let Some(filter) = &self.filter {
+            write!(buf, " filter {}", filter)?;
+        }
+        write!(buf, "\r\n")?;
+
+        Ok(())
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct LoginResponse {
+    pub callsign: Callsign,
+    pub verified: bool,
+    pub server: Option<String>,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ServerBanner {
+    pub software: String,
+    pub version: String,
+    /// Remainder of the line, usually date, server name and address
+    pub details: String,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum ServerComment {
+    /// `# logresp N0CALL verified, server T2TEST`
+    LoginResponse(LoginResponse),
+    /// `# aprsc 2.1.10-gd72a17c 18 Oct 2026 12:00:00 GMT T2TEST 1.2.3.4:14580`,
+    /// sent after connecting and as keepalive
+    Banner(ServerBanner),
+    Other(String),
+}
+
+impl From<&[u8]> for ServerComment {
+    /// Parses a server comment with or without its leading `#`
+    fn from(b: &[u8]) -> Self {
+        let s = String::from_utf8_lossy(b);
+        let s = s.strip_prefix('#').unwrap_or(&s).trim();
+
+        parse_logresp(s)
+            .map(ServerComment::LoginResponse)
+            .or_else(|| parse_banner(s).map(ServerComment::Banner))
+            .unwrap_or_else(|| ServerComment::Other(s.to_owned()))
+    }
+}
+
+fn parse_logresp(s: &str) -> Option<LoginResponse> {
+    let s = s.strip_prefix("logresp ")?;
+    let (callsign, rest) = s.split_once(' ')?;
+    let (status, rest) = match rest.split_once(',') {
+        Some((status, rest)) => (status.trim(), Some(rest.trim())),
+        None => (rest.trim(), None),
+    };
+
+    let verified = match status {
+        "verified" => true,
+        "unverified" => false,
+        _ => return None,
+    };
+
+    let server = rest
+        .and_then(|rest| rest.strip_prefix("server "))
+        .map(|server| server.trim().to_owned());
+
+    Some(LoginResponse {
+        callsign: Callsign::try_from(callsign.as_bytes()).ok()?,
+        verified,
+        server,
+    })
+}
+
+fn parse_banner(s: &str) -> Option<ServerBanner> {
+    let mut parts = s.splitn(3, ' ');
+    let software = parts.next()?;
+    let version = parts.next()?;
+
+    if !version.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(ServerBanner {
+        software: software.to_owned(),
+        version: version.to_owned(),
+        details: parts.next().unwrap_or("").trim().to_owned(),
+    })
+}
+
+/// APRS-IS connection over any stream, e.g. a `TcpStream`
+///
+/// Iterating over the connection yields the received lines.
+pub struct AprsIsConnection<S: Read + Write> {
+    reader: AprsReader<BufReader<S>>,
+}
+
+impl<S: Read + Write> AprsIsConnection<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            reader: AprsReader::new(BufReader::new(stream)),
+        }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        self.reader.get_ref().get_ref()
+    }
+
+    pub fn into_inner(self) -> S {
+        self.reader.into_inner().into_inner()
+    }
+
+    /// Sends the login line and waits for the server's `logresp`.
+    /// Banners and packets received before the response are discarded.
+    /// A wrong passcode is not an error, the response is unverified.
+    pub fn login(&mut self, login: &Login) -> Result<LoginResponse, LoginError> {
+        self.send_line(|buf| login.encode(buf))?;
+
+        // servers explain why they refuse a login before disconnecting
+        let mut last_comment = None;
+        for line in &mut self.reader {
+            if let Ok(AprsLine::ServerComment(comment)) = line?.parsed {
+                match ServerComment::from(&comment[..]) {
+                    ServerComment::LoginResponse(response) => return Ok(response),
+                    ServerComment::Other(s) if s.starts_with("logresp") => {
+                        return Err(LoginError::InvalidResponse(s));
+                    }
+                    ServerComment::Other(s) => last_comment = Some(s),
+                    ServerComment::Banner(_) => {}
+                }
+            }
+        }
+
+        Err(last_comment.map_or(LoginError::ConnectionClosed, LoginError::Rejected))
+    }
+
+    /// Changes the server-side filter of an established connection
+    pub fn send_filter(&mut self, filter: &str) -> Result<(), EncodeError> {
+        self.send_line(|buf| {
+            write!(buf, "#filter {}\r\n", filter)?;
+            Ok(())
+        })
+    }
+
+    pub fn send_packet(&mut self, packet: &AprsPacket) -> Result<(), EncodeError> {
+        self.send_line(|buf| {
+            packet.encode(buf)?;
+            buf.write_all(b"\r\n")?;
+            Ok(())
+        })
+    }
+
+    /// Sends a comment line, which servers treat as a keepalive
+    pub fn send_keepalive(&mut self, comment: &str) -> Result<(), EncodeError> {
+        self.send_line(|buf| {
+            write!(buf, "# {}\r\n", comment)?;
+            Ok(())
+        })
+    }
+
+    // lines are assembled first so a failing encoder never sends half a line
+    fn send_line<F>(&mut self, encode: F) -> Result<(), EncodeError>
+    where
+        F: FnOnce(&mut Vec<u8>) -> Result<(), EncodeError>,
+    {
+        let mut buf = vec![];
+        encode(&mut buf)?;
+
+        let stream = self.reader.get_mut().get_mut();
+        stream.write_all(&buf)?;
+        stream.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<S: Read + Write> Iterator for AprsIsConnection<S> {
+    type Item = std::io::Result<ReadLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    struct MockStream {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl MockStream {
+        fn new(input: &[u8]) -> Self {
+            Self {
+                input: Cursor::new(input.to_vec()),
+                output: vec![],
+            }
+        }
+    }
+
+    impl Read for MockStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for MockStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn passcode_works() {
+        assert_eq!(passcode(&Callsign::new("N0CALL", None)), 13023);
+        assert_eq!(passcode(&Callsign::new("n0call", Some("10"))), 13023);
+        assert_eq!(passcode(&Callsign::new("DL1ABC", None)), 17580);
+    }
+
+    #[test]
+    fn encode_login() {
+        let login = Login::new(Callsign::new("N0CALL", Some("10")), "aprs-parser", "0.2.0")
+            .passcode(13023)
+            .filter("r/48.0/12.0/50");
+
+        let mut buf = vec![];
+        login.encode(&mut buf).unwrap();
+        assert_eq!(
+            buf,
+            &b"user N0CALL-10 pass 13023 vers aprs-parser 0.2.0 filter r/48.0/12.0/50\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn encode_receive_only_login() {
+        let version = String::from("0.2.0");
+        let login = Login::new(Callsign::new("N0CALL", None), "aprs-parser", version);
+
+        let mut buf = vec![];
+        login.encode(&mut buf).unwrap();
+        assert_eq!(buf, &b"user N0CALL pass -1 vers aprs-parser 0.2.0\r\n"[..]);
+    }
+
+    #[test]
+    fn parse_logresp() {
+        assert_eq!(
+            ServerComment::from(&b"# logresp N0CALL-10 verified, server T2TEST"[..]),
+            ServerComment::LoginResponse(LoginResponse {
+                callsign: Callsign::new("N0CALL", Some("10")),
+                verified: true,
+                server: Some("T2TEST".to_owned()),
+            })
+        );
+
+        assert_eq!(
+            ServerComment::from(&b" logresp N0CALL unverified, server GLIDERN2"[..]),
+            ServerComment::LoginResponse(LoginResponse {
+                callsign: Callsign::new("N0CALL", None),
+                verified: false,
+                server: Some("GLIDERN2".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_banner() {
+        assert_eq!(
+            ServerComment::from(
+                &b"# aprsc 2.1.4-g408ed49 18 Oct 2026 12:00:00 GMT GLIDERN2 1.2.3.4:14580"[..]
+            ),
+            ServerComment::Banner(ServerBanner {
+                software: "aprsc".to_owned(),
+                version: "2.1.4-g408ed49".to_owned(),
+                details: "18 Oct 2026 12:00:00 GMT GLIDERN2 1.2.3.4:14580".to_owned(),
+            })
+        );
+
+        assert_eq!(
+            ServerComment::from(&b"# javAPRSSrvr 4.3.0b17"[..]),
+            ServerComment::Banner(ServerBanner {
+                software: "javAPRSSrvr".to_owned(),
+                version: "4.3.0b17".to_owned(),
+                details: "".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_other_comment() {
+        assert_eq!(
+            ServerComment::from(&b"# filter r/48/12/50 active"[..]),
+            ServerComment::Other("filter r/48/12/50 active".to_owned())
+        );
+    }
+
+    #[test]
+    fn login_over_stream() {
+        let stream = MockStream::new(
+            b"# aprsc 2.1.10\r\n# logresp N0CALL verified, server T2TEST\r\nN0CALL>APRS::DEST     :Hello\r\n",
+        );
+
+        let mut connection = AprsIsConnection::new(stream);
+        let login = Login::new(Callsign::new("N0CALL", None), "test", "1.0").passcode(13023);
+
+        let response = connection.login(&login).unwrap();
+        assert!(response.verified);
+        assert_eq!(response.server, Some("T2TEST".to_owned()));
+
+        connection.send_filter("p/N0").unwrap();
+
+        let line = connection.next().unwrap().unwrap();
+        assert!(matches!(line.parsed, Ok(AprsLine::Packet(_))));
+        assert!(connection.next().is_none());
+
+        assert_eq!(
+            connection.into_inner().output,
+            &b"user N0CALL pass 13023 vers test 1.0\r\n#filter p/N0\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn login_connection_closed() {
+        let mut connection = AprsIsConnection::new(MockStream::new(b"# aprsc 2.1.10\r\n"));
+        let login = Login::new(Callsign::new("N0CALL", None), "test", "1.0");
+
+        assert!(matches!(
+            connection.login(&login),
+            Err(LoginError::ConnectionClosed)
+        ));
+    }
+
+    #[test]
+    fn login_rejected() {
+        let mut connection = AprsIsConnection::new(MockStream::new(
+            b"# aprsc 2.1.10\r\n# Invalid username format\r\n",
+        ));
+        let login = Login::new(Callsign::new("N0CALL", None), "test", "1.0");
+
+        assert!(matches!(
+            connection.login(&login),
+            Err(LoginError::Rejected(s)) if s == "Invalid username format"
+        ));
+    }
+
+    #[test]
+    fn login_invalid_response() {
+        let mut connection = AprsIsConnection::new(MockStream::new(
+            b"# aprsc 2.1.10\r\n# logresp N0CALL maybe\r\n",
+        ));
+        let login = Login::new(Callsign::new("N0CALL", None), "test", "1.0");
+
+        assert!(matches!(
+            connection.login(&login),
+            Err(LoginError::InvalidResponse(s)) if s == "logresp N0CALL maybe"
+        ));
+    }
+
+    #[test]
+    fn login_io_error() {
+        struct BrokenStream;
+
+        impl Read for BrokenStream {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::ConnectionReset.into())
+            }
+        }
+
+        impl Write for BrokenStream {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut connection = AprsIsConnection::new(BrokenStream);
+        let login = Login::new(Callsign::new("N0CALL", None), "test", "1.0");
+
+        assert!(matches!(
+            connection.login(&login),
+            Err(LoginError::Io(e)) if e.kind() == std::io::ErrorKind::ConnectionReset
+        ));
+    }
+}
@@ -0,0 +1,308 @@
+// AX.25 UI frames as used by APRS on RF
+//
+// Every address is 7 bytes long: six callsign characters shifted left by one
+// bit and padded with spaces, followed by a byte containing the SSID, the
+// "has been repeated" (or command) bit and the address extension bit.
+
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use AprsData;
+use AprsError;
+use AprsPacket;
+use Callsign;
+use EncodeError;
+use EncodeWrite;
+
+const ADDRESS_LEN: usize = 7;
+const MAX_DIGIPEATERS: usize = 8;
+
+const CONTROL_UI: u8 = 0x03;
+const PID_NO_LAYER_3: u8 = 0xf0;
+
+const SSID_RESERVED_BITS: u8 = 0x60;
+const SSID_H_BIT: u8 = 0x80;
+const SSID_LAST_BIT: u8 = 0x01;
+
+impl AprsPacket {
+    /// Parses a binary AX.25 UI frame (without flags and FCS)
+    pub fn parse_ax25(b: &[u8]) -> Result<Self, AprsError> {
+        let mut addresses = vec![];
+        let mut rest = b;
+
+        loop {
+            if rest.len() < ADDRESS_LEN || addresses.len() == MAX_DIGIPEATERS + 2 {
+                return Err(AprsError::InvalidAx25Frame(b.to_owned()));
+            }
+
+            let (address, tail) = rest.split_at(ADDRESS_LEN);
+            rest = tail;
+
+            let is_last = address[6] & SSID_LAST_BIT != 0;
+            addresses.push(
+                parse_address(address).ok_or_else(|| AprsError::InvalidAx25Frame(b.to_owned()))?,
+            );
+
+            if is_last {
+                break;
+            }
+        }
+
+        if addresses.len() < 2 || rest.len() < 2 {
+            return Err(AprsError::InvalidAx25Frame(b.to_owned()));
+        }
+
+        if rest[0] != CONTROL_UI || rest[1] != PID_NO_LAYER_3 {
+            return Err(AprsError::InvalidAx25Frame(b.to_owned()));
+        }
+
+        let mut addresses = addresses.into_iter();
+        let (to, _) = addresses.next().unwrap();
+        let (from, _) = addresses.next().unwrap();
+
+        // TNC2 notation only marks the last digipeater that repeated the frame
+        let via: Vec<_> = addresses.collect();
+        let last_repeated = via.iter().rposition(|(_, repeated)| *repeated);
+        let via = via
+            .into_iter()
+            .enumerate()
+            .map(|(i, (mut call, _))| {
+                if Some(i) == last_repeated {
+                    mark_repeated(&mut call);
+                }
+                call
+            })
+            .collect();
+
+        let data = AprsData::try_from(&rest[2..])?;
+
+        Ok(AprsPacket {
+            from,
+            to,
+            via,
+            data,
+        })
+    }
+
+    /// Encodes the packet as a binary AX.25 UI frame (without flags and FCS)
+    pub fn encode_ax25<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
+        if self.via.len() > MAX_DIGIPEATERS {
+            return Err(EncodeError::TooManyDigipeaters(self.via.len()));
+        }
+
+        // AX.25 v2 command frame: C bit set on the destination only
+        encode_address(&self.to, SSID_H_BIT, false, buf)?;
+        encode_address(&self.from, 0, self.via.is_empty(), buf)?;
+
+        // a digipeater marked with `*` implies all previous ones repeated the frame as well
+        let last_repeated = self.via.iter().rposition(is_repeated);
+        for (i, v) in self.via.iter().enumerate() {
+            let h = match last_repeated {
+                Some(last) if i <= last => SSID_H_BIT,
+                _ => 0,
+            };
+            encode_address(v, h, i + 1 == self.via.len(), buf)?;
+        }
+
+        buf.write_all(&[CONTROL_UI, PID_NO_LAYER_3])?;
+        self.data.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+fn parse_address(b: &[u8]) -> Option<(Callsign, bool)> {
+    let mut call = String::with_capacity(6);
+    for c in &b[..6] {
+        if c & 0x01 != 0 {
+            return None;
+        }
+
+        let c = (c >> 1) as char;
+        if c == ' ' {
+            break;
+        }
+        if !c.is_ascii_alphanumeric() {
+            return None;
+        }
+        call.push(c);
+    }
+
+    if call.is_empty() {
+        return None;
+    }
+
+    let ssid = (b[6] >> 1) & 0x0f;
+    let ssid = if ssid == 0 {
+        None
+    } else {
+        Some(ssid.to_string())
+    };
+
+    Some((Callsign { call, ssid }, b[6] & SSID_H_BIT != 0))
+}
+
+fn encode_address<W: EncodeWrite>(
+    callsign: &Callsign,
+    h: u8,
+    last: bool,
+    buf: &mut W,
+) -> Result<(), EncodeError> {
+    let invalid = || EncodeError::InvalidAx25Callsign(callsign.to_string());
+
+    let (call, ssid) = match &callsign.ssid {
+        Some(ssid) => (callsign.call.as_str(), ssid.trim_end_matches('*')),
+        None => (callsign.call.trim_end_matches('*'), ""),
+    };
+
+    if call.is_empty()
+        || call.len() > 6
+        || !call
+            .bytes()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let ssid = match ssid {
+        "" => 0,
+        s => s
+            .parse::<u8>()
+            .ok()
+            .filter(|s| *s <= 15)
+            .ok_or_else(invalid)?,
+    };
+
+    let mut address = [b' ' << 1; ADDRESS_LEN];
+    for (i, c) in call.bytes().enumerate() {
+        address[i] = c << 1;
+    }
+    address[6] = SSID_RESERVED_BITS | h | (ssid << 1) | u8::from(last);
+
+    buf.write_all(&address)?;
+
+    Ok(())
+}
+
+fn is_repeated(callsign: &Callsign) -> bool {
+    match &callsign.ssid {
+        Some(ssid) => ssid.ends_with('*'),
+        None => callsign.call.ends_with('*'),
+    }
+}
+
+fn mark_repeated(callsign: &mut Callsign) {
+    match &mut callsign.ssid {
+        Some(ssid) => ssid.push('*'),
+        None => callsign.call.push('*'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame() -> Vec<u8> {
+        let mut frame = vec![];
+        frame.extend_from_slice(&[
+            b'A' << 1,
+            b'P' << 1,
+            b'R' << 1,
+            b'S' << 1,
+            b' ' << 1,
+            b' ' << 1,
+            0xe0,
+        ]);
+        frame.extend_from_slice(&[
+            b'N' << 1,
+            b'0' << 1,
+            b'C' << 1,
+            b'A' << 1,
+            b'L' << 1,
+            b'L' << 1,
+            0x60 | (7 << 1),
+        ]);
+        frame.extend_from_slice(&[
+            b'W' << 1,
+            b'I' << 1,
+            b'D' << 1,
+            b'E' << 1,
+            b'1' << 1,
+            b' ' << 1,
+            0xe0 | (1 << 1),
+        ]);
+        frame.extend_from_slice(&[
+            b'W' << 1,
+            b'I' << 1,
+            b'D' << 1,
+            b'E' << 1,
+            b'2' << 1,
+            b' ' << 1,
+            0x60 | (2 << 1) | 1,
+        ]);
+        frame.extend_from_slice(&[0x03, 0xf0]);
+        frame.extend_from_slice(b"!4903.50N/07201.75W-Test");
+        frame
+    }
+
+    #[test]
+    fn parse_frame() {
+        let packet = AprsPacket::parse_ax25(&frame()).unwrap();
+
+        assert_eq!(packet.from, Callsign::new("N0CALL", Some("7")));
+        assert_eq!(packet.to, Callsign::new("APRS", None));
+        assert_eq!(
+            packet.via,
+            vec![
+                Callsign::new("WIDE1", Some("1*")),
+                Callsign::new("WIDE2", Some("2"))
+            ]
+        );
+        assert!(matches!(packet.data, AprsData::Position(_)));
+    }
+
+    #[test]
+    fn encode_frame() {
+        let packet =
+            AprsPacket::try_from(&b"N0CALL-7>APRS,WIDE1-1*,WIDE2-2:!4903.50N/07201.75W-Test"[..])
+                .unwrap();
+
+        let mut buf = vec![];
+        packet.encode_ax25(&mut buf).unwrap();
+        assert_eq!(buf, frame());
+    }
+
+    #[test]
+    fn parse_truncated_frame() {
+        let frame = frame();
+        assert_eq!(
+            AprsPacket::parse_ax25(&frame[..20]),
+            Err(AprsError::InvalidAx25Frame(frame[..20].to_vec()))
+        );
+    }
+
+    #[test]
+    fn parse_non_ui_frame() {
+        let mut frame = frame();
+        frame[28] = 0x3f;
+        assert_eq!(
+            AprsPacket::parse_ax25(&frame),
+            Err(AprsError::InvalidAx25Frame(frame.clone()))
+        );
+    }
+
+    #[test]
+    fn encode_invalid_callsign() {
+        let packet =
+            AprsPacket::try_from(&br"ICA3D17F2>APRS,qAS,dl4mea:/074849h4821.61N\01224.49E^"[..])
+                .unwrap();
+
+        let mut buf = vec![];
+        assert!(matches!(
+            packet.encode_ax25(&mut buf),
+            Err(EncodeError::InvalidAx25Callsign(c)) if c == "ICA3D17F2"
+        ));
+    }
+}
@@ -1,31 +1,31 @@
-use std::io::Write;
-
 use EncodeError;
+use EncodeWrite;
 
-pub(crate) fn encode_ascii<W: Write>(
-    val: f64,
+pub(crate) fn encode_ascii<W: EncodeWrite>(
+    mut val: u32,
     buf: &mut W,
     padding: usize,
 ) -> Result<(), EncodeError> {
-    let mut val = val.round();
-    let mut digit_buf = vec![];
-
-    debug_assert!(!val.is_nan() && val > 0.0 && !val.is_infinite());
-
-    while val > 1.0 {
-        let x = val % 91.0;
-        val /= 91.0;
-
-        digit_buf.push(digit_to_ascii(x as u8));
+    // filled from the back, enough for any u32
+    let mut digit_buf = [0; 5];
+    let mut start = digit_buf.len();
+
+    loop {
+        start -= 1;
+        digit_buf[start] = digit_to_ascii((val % 91) as u8);
+        val /= 91;
+
+        if val == 0 {
+            break;
+        }
     }
 
     // pad with zeroes
-    for _ in digit_buf.len()..padding {
+    for _ in digit_buf.len() - start..padding {
         buf.write_all(&[digit_to_ascii(0)])?;
     }
 
-    digit_buf.reverse();
-    buf.write_all(&digit_buf)?;
+    buf.write_all(&digit_buf[start..])?;
 
     Ok(())
 }
@@ -34,7 +34,7 @@ pub(crate) fn decode_ascii(bytes: &[u8]) -> Option<f64> {
     let mut val = 0.0;
 
     for b in bytes {
-        let x = digit_from_ascii(*b)?;
+        let x = digit_from_ascii(*b).filter(|x| *x < 91)?;
 
         val *= 91.0;
         val += x as f64;
@@ -58,7 +58,7 @@ mod tests {
 
     #[test]
     fn encode_works() {
-        let val = 20427156.0;
+        let val = 20427156;
         let expected = &b"<*e7"[..];
 
         let mut buf = vec![];
@@ -68,7 +68,7 @@ mod tests {
 
     #[test]
     fn encode_with_padding() {
-        let val = 20427156.0;
+        let val = 20427156;
         let expected = &b"!!!!<*e7"[..];
 
         let mut buf = vec![];
@@ -78,7 +78,7 @@ mod tests {
 
     #[test]
     fn encode_with_under_padding() {
-        let val = 20427156.0;
+        let val = 20427156;
         let expected = &b"<*e7"[..];
 
         let mut buf = vec![];
@@ -98,14 +98,39 @@ mod tests {
     fn decode_invalid_digits_returns_none() {
         let ascii = &b"<* 1"[..];
         assert_eq!(None, decode_ascii(ascii));
+
+        let ascii = &b"<*|1"[..];
+        assert_eq!(None, decode_ascii(ascii));
+    }
+
+    #[test]
+    fn encode_zero() {
+        let mut buf = vec![];
+        encode_ascii(0, &mut buf, 4).unwrap();
+        assert_eq!(&b"!!!!"[..], buf);
+
+        let mut buf = vec![];
+        encode_ascii(0, &mut buf, 0).unwrap();
+        assert_eq!(&b"!"[..], buf);
+    }
+
+    #[test]
+    fn encode_exact_powers() {
+        let mut buf = vec![];
+        encode_ascii(91 * 91 * 91 + 1, &mut buf, 4).unwrap();
+        assert_eq!(&b"\"!!\""[..], buf);
+
+        let mut buf = vec![];
+        encode_ascii(u32::MAX, &mut buf, 4).unwrap();
+        assert_eq!(decode_ascii(&buf), Some(u32::MAX as f64));
     }
 
     #[test]
     fn edge_case() {
         let ascii = &b"#$%^"[..];
-        let num = 1532410.0;
+        let num = 1532410;
 
-        assert_eq!(num, decode_ascii(ascii).unwrap());
+        assert_eq!(num as f64, decode_ascii(ascii).unwrap());
 
         let mut buf = vec![];
         encode_ascii(num, &mut buf, 4).unwrap();
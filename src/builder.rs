@@ -0,0 +1,467 @@
+// builders for outgoing packets
+//
+// The builders take plain values, validate them on `build()` and fill in the
+// wire-level details: symbol overlays, the compressed `cs` bytes or the
+// `CSE/SPD` data extension, and the `/A=` altitude comment.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use compression_type::{GpsFix, NmeaSource, Origin};
+use math;
+use AprsCompressedCs;
+use AprsCompressionType;
+use AprsCourseSpeed;
+use AprsCst;
+use AprsData;
+use AprsMessage;
+use AprsPacket;
+use AprsPosition;
+use Callsign;
+use EncodeError;
+use EncodeWrite;
+use Latitude;
+use Longitude;
+use Symbol;
+use Timestamp;
+
+/// Builds an [`AprsPosition`]
+///
+/// ```rust
+/// # extern crate aprs_parser;
+/// # use aprs_parser::{PositionBuilder, Symbol};
+/// let position = PositionBuilder::new(48.3601, 12.4081)
+///     .symbol(Symbol::CAR)
+///     .course_speed(90, 25.0)
+///     .altitude(1500.0)
+///     .comment("on the road")
+///     .build()
+///     .unwrap();
+///
+/// let mut buf = vec![];
+/// position.encode(&mut buf).unwrap();
+/// assert_eq!(buf, &b"!4821.61N/01224.49E>090/025/A=001500on the road"[..]);
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub struct PositionBuilder {
+    latitude: f64,
+    longitude: f64,
+    timestamp: Option<Timestamp>,
+    messaging_supported: bool,
+    symbol: Symbol,
+    course_speed: Option<(u16, f64)>,
+    altitude_feet: Option<f64>,
+    comment: Vec<u8>,
+    compressed: bool,
+}
+
+impl PositionBuilder {
+    /// Starts an uncompressed position without timestamp, using the
+    /// "X" symbol `/.`
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+            timestamp: None,
+            messaging_supported: false,
+            symbol: Symbol::new('/', '.').unwrap(),
+            course_speed: None,
+            altitude_feet: None,
+            comment: Vec::new(),
+            compressed: false,
+        }
+    }
+
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn messaging_supported(mut self, messaging_supported: bool) -> Self {
+        self.messaging_supported = messaging_supported;
+        self
+    }
+
+    pub fn symbol(mut self, symbol: Symbol) -> Self {
+        self.symbol = symbol;
+        self
+    }
+
+    /// Course in degrees (1-360, 0 if unknown) and speed in knots (0-999)
+    pub fn course_speed(mut self, course_degrees: u16, speed_knots: f64) -> Self {
+        self.course_speed = Some((course_degrees, speed_knots));
+        self
+    }
+
+    /// Altitude in feet, encoded as `/A=` in the comment
+    pub fn altitude(mut self, altitude_feet: f64) -> Self {
+        self.altitude_feet = Some(altitude_feet);
+        self
+    }
+
+    pub fn comment<T: AsRef<[u8]>>(mut self, comment: T) -> Self {
+        self.comment = comment.as_ref().to_vec();
+        self
+    }
+
+    /// Uses the compressed position format, which is shorter and more precise
+    pub fn compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    pub fn build(self) -> Result<AprsPosition, EncodeError> {
+        let latitude =
+            Latitude::new(self.latitude).ok_or(EncodeError::InvalidLatitude(self.latitude))?;
+        let longitude =
+            Longitude::new(self.longitude).ok_or(EncodeError::InvalidLongitude(self.longitude))?;
+
+        if let Some(timestamp) = &self.timestamp {
+            if timestamp.validate().is_err() || matches!(timestamp, Timestamp::MDHM(..)) {
+                return Err(EncodeError::InvalidTimestamp(timestamp.clone()));
+            }
+        }
+
+        let symbol_table = match self.symbol.table() {
+            // compressed positions use `a-j` for numeric overlays
+            table @ '0'..='9' if self.compressed => (table as u8 - b'0' + b'a') as char,
+            table => table,
+        };
+
+        if let Some((course, speed)) = self.course_speed {
+            if course > 360 {
+                return Err(EncodeError::InvalidCourse(course));
+            }
+            if !(0.0..=999.0).contains(&speed) {
+                return Err(EncodeError::InvalidSpeed(speed));
+            }
+        }
+
+        if self.comment.iter().any(|b| *b == b'\r' || *b == b'\n') {
+            return Err(EncodeError::InvalidComment(self.comment));
+        }
+
+        let mut comment = Vec::new();
+
+        let cst = match (self.compressed, self.course_speed) {
+            (true, Some((course, speed))) => AprsCst::CompressedSome {
+                // 360 would be encoded as 90, which marks a radio range
+                cs: AprsCompressedCs::CourseSpeed(AprsCourseSpeed::new(course % 360, speed)),
+                t: AprsCompressionType {
+                    gps_fix: GpsFix::Current,
+                    nmea_source: NmeaSource::Other,
+                    origin: Origin::Software,
+                },
+            },
+            (true, None) => AprsCst::CompressedNone(*b" sT"),
+            (false, course_speed) => {
+                if let Some((course, speed)) = course_speed {
+                    let speed = math::round(speed) as u16;
+                    write!(comment, "{:03}/{:03}", course, speed)?;
+                }
+                AprsCst::Uncompressed
+            }
+        };
+
+        if let Some(altitude) = self.altitude_feet {
+            let altitude = math::round(altitude);
+            if !(-99999.0..=999999.0).contains(&altitude) {
+                return Err(EncodeError::InvalidAltitude(altitude));
+            }
+            write!(comment, "/A={:06}", altitude as i32)?;
+        }
+
+        comment.extend_from_slice(&self.comment);
+
+        Ok(AprsPosition {
+            timestamp: self.timestamp,
+            messaging_supported: self.messaging_supported,
+            latitude,
+            longitude,
+            symbol_table,
+            symbol_code: self.symbol.code(),
+            comment,
+            cst,
+        })
+    }
+}
+
+/// Builds an [`AprsPacket`]
+///
+/// ```rust
+/// # extern crate aprs_parser;
+/// # use aprs_parser::{PacketBuilder, PositionBuilder};
+/// let packet = PacketBuilder::new("N0CALL-7")
+///     .to("APZ001")
+///     .path(&["WIDE1-1", "WIDE2-1"])
+///     .data(PositionBuilder::new(48.3601, 12.4081).build().unwrap())
+///     .build()
+///     .unwrap();
+///
+/// let mut buf = vec![];
+/// packet.encode(&mut buf).unwrap();
+/// assert_eq!(buf, &b"N0CALL-7>APZ001,WIDE1-1,WIDE2-1:!4821.61N/01224.49E."[..]);
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub struct PacketBuilder {
+    from: String,
+    to: String,
+    path: Vec<String>,
+    data: Option<AprsData>,
+}
+
+impl PacketBuilder {
+    /// Starts a packet from `from` to the generic destination `APRS`
+    pub fn new<T: AsRef<str>>(from: T) -> Self {
+        Self {
+            from: from.as_ref().to_string(),
+            to: "APRS".to_string(),
+            path: Vec::new(),
+            data: None,
+        }
+    }
+
+    /// Destination, usually the software or device identifier
+    pub fn to<T: AsRef<str>>(mut self, to: T) -> Self {
+        self.to = to.as_ref().to_string();
+        self
+    }
+
+    /// Digipeater path like `WIDE1-1,WIDE2-1`
+    pub fn path<I>(mut self, path: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.path = path.into_iter().map(|c| c.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn data<T: Into<AprsData>>(mut self, data: T) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Validates the callsigns, fails with [`EncodeError::InvalidData`]
+    /// without data.
+    pub fn build(self) -> Result<AprsPacket, EncodeError> {
+        let data = self.data.ok_or(EncodeError::InvalidData)?;
+
+        Ok(AprsPacket {
+            from: parse_callsign(&self.from)?,
+            to: parse_callsign(&self.to)?,
+            via: self
+                .path
+                .iter()
+                .map(|c| parse_callsign(c))
+                .collect::<Result<_, _>>()?,
+            data,
+        })
+    }
+}
+
+impl From<Callsign> for PacketBuilder {
+    fn from(from: Callsign) -> Self {
+        Self::new(from.to_string())
+    }
+}
+
+impl From<AprsPosition> for AprsData {
+    fn from(position: AprsPosition) -> Self {
+        Self::Position(position)
+    }
+}
+
+impl From<AprsMessage> for AprsData {
+    fn from(message: AprsMessage) -> Self {
+        Self::Message(message)
+    }
+}
+
+// up to 9 alphanumeric characters and an optional alphanumeric SSID
+fn parse_callsign(s: &str) -> Result<Callsign, EncodeError> {
+    let valid = |part: &str, max: usize| {
+        !part.is_empty() && part.len() <= max && part.bytes().all(|b| b.is_ascii_alphanumeric())
+    };
+
+    let valid = match s.split_once('-') {
+        Some((call, ssid)) => valid(call, 9) && valid(ssid, 2),
+        None => valid(s, 9),
+    };
+    if !valid {
+        return Err(EncodeError::InvalidCallsign(s.to_string()));
+    }
+
+    Callsign::try_from(s.as_bytes()).map_err(|_| EncodeError::InvalidCallsign(s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AprsCompressedCs;
+
+    fn encode(position: AprsPosition) -> Vec<u8> {
+        let mut buf = vec![];
+        position.encode(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn uncompressed_position() {
+        let position = PositionBuilder::new(48.3601, -12.4081)
+            .timestamp(Timestamp::HHMMSS(7, 48, 49))
+            .messaging_supported(true)
+            .symbol(Symbol::new('\\', '^').unwrap())
+            .comment("Hello")
+            .build()
+            .unwrap();
+
+        assert_eq!(encode(position), &b"@074849h4821.61N\\01224.49W^Hello"[..]);
+    }
+
+    #[test]
+    fn uncompressed_course_speed_altitude() {
+        let position = PositionBuilder::new(-48.3601, 12.4081)
+            .symbol(Symbol::CAR)
+            .course_speed(360, 12.6)
+            .altitude(-12.0)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            encode(position),
+            &b"!4821.61S/01224.49E>360/013/A=-00012"[..]
+        );
+    }
+
+    #[test]
+    fn compressed_position() {
+        let position = PositionBuilder::new(48.3601, 12.4081)
+            .symbol(Symbol::new('3', '#').unwrap())
+            .course_speed(360, 36.2)
+            .altitude(1234.0)
+            .compressed(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            position.cst,
+            AprsCst::CompressedSome {
+                cs: AprsCompressedCs::CourseSpeed(AprsCourseSpeed::new(0, 36.2)),
+                t: AprsCompressionType {
+                    gps_fix: GpsFix::Current,
+                    nmea_source: NmeaSource::Other,
+                    origin: Origin::Software,
+                },
+            }
+        );
+
+        let encoded = encode(position);
+        assert_eq!(encoded[1], b'd');
+        assert!(encoded.ends_with(b"/A=001234"));
+
+        let parsed = AprsPosition::try_from(&encoded[..]).unwrap();
+        assert_relative_eq!(*parsed.latitude, 48.3601, epsilon = 0.0001);
+        assert_relative_eq!(*parsed.longitude, 12.4081, epsilon = 0.0001);
+        assert_eq!(parsed.symbol_code, '#');
+    }
+
+    #[test]
+    fn compressed_position_without_course() {
+        let position = PositionBuilder::new(0.0, 0.0)
+            .compressed(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(position.cst, AprsCst::CompressedNone(*b" sT"));
+    }
+
+    #[test]
+    fn invalid_position() {
+        let build = |builder: PositionBuilder| builder.build().unwrap_err();
+
+        assert!(matches!(
+            build(PositionBuilder::new(90.1, 0.0)),
+            EncodeError::InvalidLatitude(_)
+        ));
+        assert!(matches!(
+            build(PositionBuilder::new(0.0, -180.1)),
+            EncodeError::InvalidLongitude(_)
+        ));
+        assert!(matches!(
+            build(PositionBuilder::new(0.0, 0.0).course_speed(361, 0.0)),
+            EncodeError::InvalidCourse(361)
+        ));
+        assert!(matches!(
+            build(PositionBuilder::new(0.0, 0.0).course_speed(0, -1.0)),
+            EncodeError::InvalidSpeed(_)
+        ));
+        assert!(matches!(
+            build(PositionBuilder::new(0.0, 0.0).altitude(1e6)),
+            EncodeError::InvalidAltitude(_)
+        ));
+        assert!(matches!(
+            build(PositionBuilder::new(0.0, 0.0).comment("a\r\nb")),
+            EncodeError::InvalidComment(_)
+        ));
+        assert!(matches!(
+            build(PositionBuilder::new(0.0, 0.0).timestamp(Timestamp::HHMMSS(65, 43, 21))),
+            EncodeError::InvalidTimestamp(_)
+        ));
+    }
+
+    #[test]
+    fn packet() {
+        let message = AprsMessage {
+            addressee: b"DEST".to_vec(),
+            text: b"Hello".to_vec(),
+            id: None,
+        };
+
+        let packet = PacketBuilder::from(Callsign::new("N0CALL", Some("7")))
+            .path(&["WIDE1-1", "qAR", "IGATE"])
+            .data(message.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            packet,
+            AprsPacket {
+                from: Callsign::new("N0CALL", Some("7")),
+                to: Callsign::new("APRS", None),
+                via: vec![
+                    Callsign::new("WIDE1", Some("1")),
+                    Callsign::new("qAR", None),
+                    Callsign::new("IGATE", None),
+                ],
+                data: AprsData::Message(message),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_packet() {
+        let position = PositionBuilder::new(0.0, 0.0).build().unwrap();
+
+        assert!(matches!(
+            PacketBuilder::new("N0CALL").build(),
+            Err(EncodeError::InvalidData)
+        ));
+
+        for call in &["", "N0CALL-", "-1", "N0 CALL", "TOOLONGCALL", "N0CALL-123"] {
+            assert!(matches!(
+                PacketBuilder::new(call).data(position.clone()).build(),
+                Err(EncodeError::InvalidCallsign(ref c)) if c == call
+            ));
+        }
+
+        assert!(matches!(
+            PacketBuilder::new("N0CALL")
+                .path(&["WIDE1-1", "WIDE2*"])
+                .data(position)
+                .build(),
+            Err(EncodeError::InvalidCallsign(ref c)) if c == "WIDE2*"
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 // functions for working with byte arrays
 
-pub fn parse_bytes<T: std::str::FromStr>(b: &[u8]) -> Option<T> {
-    std::str::from_utf8(b).ok()?.parse().ok()
+pub fn parse_bytes<T: core::str::FromStr>(b: &[u8]) -> Option<T> {
+    core::str::from_utf8(b).ok()?.parse().ok()
 }
 
 #[cfg(test)]
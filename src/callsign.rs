@@ -1,5 +1,7 @@
-use std::convert::TryFrom;
-use std::fmt::{Display, Formatter};
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use core::convert::TryFrom;
+use core::fmt::{Display, Formatter};
 
 use AprsError;
 
@@ -22,7 +24,40 @@ impl TryFrom<&[u8]> for Callsign {
     type Error = AprsError;
 
     fn try_from(b: &[u8]) -> Result<Self, Self::Error> {
-        let s = std::str::from_utf8(b).map_err(|_| AprsError::NonUtf8Callsign(b.to_owned()))?;
+        CallsignRef::try_from(b).map(CallsignRef::into_owned)
+    }
+}
+
+impl Display for Callsign {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", self.call)?;
+
+        if let Some(ssid) = &self.ssid {
+            write!(f, "-{}", ssid)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Borrowed version of [`Callsign`]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct CallsignRef<'a> {
+    pub call: &'a str,
+    pub ssid: Option<&'a str>,
+}
+
+impl<'a> CallsignRef<'a> {
+    pub fn into_owned(self) -> Callsign {
+        Callsign::new(self.call, self.ssid)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for CallsignRef<'a> {
+    type Error = AprsError;
+
+    fn try_from(b: &'a [u8]) -> Result<Self, Self::Error> {
+        let s = core::str::from_utf8(b).map_err(|_| AprsError::NonUtf8Callsign(b.to_owned()))?;
 
         match s.split_once('-') {
             Some((call, ssid)) => {
@@ -31,20 +66,26 @@ impl TryFrom<&[u8]> for Callsign {
                 } else if ssid.is_empty() {
                     Err(AprsError::EmptySSID(s.to_owned()))
                 } else {
-                    Ok(Callsign::new(call, Some(ssid)))
+                    Ok(CallsignRef {
+                        call,
+                        ssid: Some(ssid),
+                    })
                 }
             }
 
-            None => Ok(Callsign::new(s, None)),
+            None => Ok(CallsignRef {
+                call: s,
+                ssid: None,
+            }),
         }
     }
 }
 
-impl Display for Callsign {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+impl<'a> Display for CallsignRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(f, "{}", self.call)?;
 
-        if let Some(ssid) = &self.ssid {
+        if let Some(ssid) = self.ssid {
             write!(f, "-{}", ssid)?;
         }
 
@@ -55,7 +96,8 @@ impl Display for Callsign {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::convert::TryInto;
+    use alloc::string::ToString;
+    use core::convert::TryInto;
 
     #[test]
     fn parse_callsign() {
@@ -97,6 +139,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_borrowed() {
+        let b = &b"ABCDEF-42"[..];
+        let callsign = CallsignRef::try_from(b).unwrap();
+        assert_eq!(
+            callsign,
+            CallsignRef {
+                call: "ABCDEF",
+                ssid: Some("42")
+            }
+        );
+        assert_eq!(callsign.into_owned(), Callsign::new("ABCDEF", Some("42")));
+        assert_eq!(callsign.to_string(), "ABCDEF-42");
+    }
+
     #[test]
     fn display_no_ssid() {
         assert_eq!("ABCDEF", format!("{}", Callsign::new("ABCDEF", None)));
@@ -0,0 +1,95 @@
+// time sources
+//
+// Stateful helpers like the message session ask a `Clock` for the current
+// time instead of reading the system time themselves, so tests and
+// simulations can drive them with a `ManualClock`. Times are durations since
+// an arbitrary, fixed starting point.
+
+use core::cell::Cell;
+use core::time::Duration;
+
+pub trait Clock {
+    /// Monotonic time since an arbitrary starting point
+    fn now(&self) -> Duration;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// A clock that only moves when told to, for tests
+#[derive(Debug, Default, Clone)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+}
+
+impl ManualClock {
+    /// Starts at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, now: Duration) {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// The monotonic system clock, starting at zero when created
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock() {
+        let clock = ManualClock::new();
+        assert_eq!(clock.now(), Duration::from_secs(0));
+
+        clock.advance(Duration::from_secs(5));
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(Clock::now(&&clock), Duration::from_millis(5500));
+
+        clock.set(Duration::from_secs(1));
+        assert_eq!(clock.now(), Duration::from_secs(1));
+    }
+}
@@ -1,12 +1,16 @@
-use std::io::Write;
-
 use base91;
 use compression_type::NmeaSource;
+use math;
+use Angle;
 use AprsCompressionType;
 use AprsError;
 use EncodeError;
+use EncodeWrite;
+use Length;
+use Speed;
 
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AprsCompressedCs {
     CourseSpeed(AprsCourseSpeed),
     RadioRange(AprsRadioRange),
@@ -33,7 +37,7 @@ impl AprsCompressedCs {
         }
     }
 
-    pub(crate) fn encode<W: Write>(
+    pub(crate) fn encode<W: EncodeWrite>(
         self,
         buf: &mut W,
         t: AprsCompressionType,
@@ -64,6 +68,7 @@ impl AprsCompressedCs {
 }
 
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AprsCourseSpeed {
     course_degrees: u16,
     speed_knots: f64,
@@ -72,7 +77,7 @@ pub struct AprsCourseSpeed {
 impl AprsCourseSpeed {
     pub fn new(course_degrees: u16, speed_knots: f64) -> Self {
         assert!(course_degrees <= 360);
-        assert!(speed_knots < (1.08_f64).powi(255));
+        assert!(speed_knots < math::powi(1.08, 255));
 
         Self {
             course_degrees,
@@ -88,12 +93,20 @@ impl AprsCourseSpeed {
         self.speed_knots
     }
 
+    pub fn course(&self) -> Angle {
+        Angle::from_degrees(self.course_degrees as f64)
+    }
+
+    pub fn speed(&self) -> Speed {
+        Speed::from_knots(self.speed_knots)
+    }
+
     fn from_cs(c: u8, s: u8) -> Self {
         let course_degrees = c as u16 * 4;
-        let speed_knots = ((1.08_f64).powi(s as i32) - 1.0) as f64;
+        let speed_knots = math::powi(1.08, s as i32) - 1.0;
 
         debug_assert!(course_degrees <= 360);
-        debug_assert!(speed_knots < (1.08_f64).powi(255));
+        debug_assert!(speed_knots < math::powi(1.08, 255));
 
         Self {
             course_degrees,
@@ -103,20 +116,21 @@ impl AprsCourseSpeed {
 
     fn to_cs(self) -> (u8, u8) {
         let c = self.course_degrees / 4;
-        let s = ((self.speed_knots + 1.0).ln() / (1.08_f64).ln()).round();
+        let s = math::round(math::ln(self.speed_knots + 1.0) / math::ln(1.08));
 
         (c as u8, s as u8)
     }
 }
 
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AprsRadioRange {
     range_miles: f64,
 }
 
 impl AprsRadioRange {
     pub fn new(range_miles: f64) -> Self {
-        assert!(range_miles < (1.08_f64).powi(255));
+        assert!(range_miles < math::powi(1.08, 255));
 
         Self { range_miles }
     }
@@ -125,27 +139,32 @@ impl AprsRadioRange {
         self.range_miles
     }
 
+    pub fn range(&self) -> Length {
+        Length::from_statute_miles(self.range_miles)
+    }
+
     fn from_s(s: u8) -> Self {
         Self {
-            range_miles: (2.0 * (1.08_f64).powi(s as i32)) as f64,
+            range_miles: 2.0 * math::powi(1.08, s as i32),
         }
     }
 
     fn to_s(self) -> u8 {
-        let s = ((self.range_miles / 2.0).ln() / (1.08_f64).ln()).round();
+        let s = math::round(math::ln(self.range_miles / 2.0) / math::ln(1.08));
 
         s as u8
     }
 }
 
 #[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AprsAltitude {
     altitude_feet: f64,
 }
 
 impl AprsAltitude {
     pub fn new(altitude_feet: f64) -> Self {
-        assert!(altitude_feet < (1.002_f64).powi(255 * 91 + 255));
+        assert!(altitude_feet < math::powi(1.002, 255 * 91 + 255));
 
         Self { altitude_feet }
     }
@@ -154,14 +173,18 @@ impl AprsAltitude {
         self.altitude_feet
     }
 
+    pub fn altitude(&self) -> Length {
+        Length::from_feet(self.altitude_feet)
+    }
+
     fn from_cs(c: u8, s: u8) -> Self {
         Self {
-            altitude_feet: ((1.002_f64).powi(c as i32 * 91 + s as i32)) as f64,
+            altitude_feet: math::powi(1.002, c as i32 * 91 + s as i32),
         }
     }
 
     fn to_cs(self) -> (u8, u8) {
-        let alt = (self.altitude_feet.ln() / (1.002_f64).ln()).round() as i32;
+        let alt = math::round(math::ln(self.altitude_feet) / math::ln(1.002)) as i32;
 
         let c = alt / 91;
         let s = alt % 91;
@@ -185,6 +208,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn quantities() {
+        let cs = AprsCourseSpeed::new(90, 10.0);
+        assert_relative_eq!(cs.course().radians(), core::f64::consts::FRAC_PI_2);
+        assert_relative_eq!(cs.speed().kilometers_per_hour(), 18.52);
+
+        assert_relative_eq!(AprsRadioRange::new(10.0).range().kilometers(), 16.09344);
+        assert_relative_eq!(AprsAltitude::new(1000.0).altitude().meters(), 304.8);
+    }
+
     #[test]
     fn radio_range_exhaustive() {
         for s in 0..91 {
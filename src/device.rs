@@ -0,0 +1,311 @@
+// device identification
+//
+// The destination callsign of most packets identifies the software or device
+// that sent it, e.g. `APDR16` for APRSdroid. Mic-E packets use their
+// destination for the latitude instead and identify the device with
+// characters at the start and end of the comment.
+//
+// The embedded table in `devices.tsv` covers common devices, newer tables in
+// the same format can be loaded at runtime.
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use AprsData;
+use AprsPacket;
+use Callsign;
+use DeviceTableError;
+
+const EMBEDDED_TABLE: &str = include_str!("devices.tsv");
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum DeviceClass {
+    Ht,
+    Rig,
+    Tracker,
+    App,
+    Software,
+    Digi,
+    Wx,
+    Dstar,
+    Other(String),
+}
+
+impl From<&str> for DeviceClass {
+    fn from(s: &str) -> Self {
+        match s {
+            "ht" => Self::Ht,
+            "rig" => Self::Rig,
+            "tracker" => Self::Tracker,
+            "app" => Self::App,
+            "software" => Self::Software,
+            "digi" => Self::Digi,
+            "wx" => Self::Wx,
+            "dstar" => Self::Dstar,
+            other => Self::Other(other.to_owned()),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Device {
+    pub vendor: String,
+    pub model: String,
+    pub class: DeviceClass,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+struct TocallEntry {
+    pattern: String,
+    device: Device,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+struct MicEEntry {
+    first: String,
+    suffix: String,
+    device: Device,
+}
+
+/// Lookup table from destination callsigns and Mic-E comments to devices
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct DeviceTable {
+    tocalls: Vec<TocallEntry>,
+    mice: Vec<MicEEntry>,
+}
+
+impl DeviceTable {
+    /// The table shipped with this crate
+    pub fn embedded() -> Self {
+        Self::parse(EMBEDDED_TABLE).expect("embedded device table is valid")
+    }
+
+    /// Parses a table in the format of the embedded `devices.tsv`
+    pub fn parse(s: &str) -> Result<Self, DeviceTableError> {
+        let mut table = Self {
+            tocalls: vec![],
+            mice: vec![],
+        };
+
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || DeviceTableError::InvalidLine {
+                line: i + 1,
+                content: line.to_owned(),
+            };
+
+            let fields: Vec<_> = line.split('\t').collect();
+            let device = |fields: &[&str]| match *fields {
+                [vendor, model, class] if !class.is_empty() => Some(Device {
+                    vendor: vendor.to_owned(),
+                    model: model.to_owned(),
+                    class: class.into(),
+                }),
+                _ => None,
+            };
+
+            match fields[..] {
+                ["tocall", pattern, ref rest @ ..] if !pattern.is_empty() => {
+                    table.tocalls.push(TocallEntry {
+                        pattern: pattern.to_owned(),
+                        device: device(rest).ok_or_else(invalid)?,
+                    })
+                }
+                ["mice", first, suffix, ref rest @ ..] if !first.is_empty() => {
+                    table.mice.push(MicEEntry {
+                        first: first.to_owned(),
+                        suffix: suffix.to_owned(),
+                        device: device(rest).ok_or_else(invalid)?,
+                    })
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Loads a table from a file
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, DeviceTableError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Looks up the device for a destination callsign, ignoring its SSID.
+    /// The most specific pattern wins.
+    pub fn lookup_tocall(&self, tocall: &Callsign) -> Option<&Device> {
+        self.tocalls
+            .iter()
+            .filter_map(|entry| {
+                pattern_specificity(&entry.pattern, &tocall.call).map(|s| (s, &entry.device))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, device)| device)
+    }
+
+    /// Looks up the device for the comment of a Mic-E packet, the part after
+    /// the symbol table identifier. The longest matching suffix wins.
+    pub fn lookup_mice(&self, comment: &[u8]) -> Option<&Device> {
+        let (first, rest) = comment.split_first()?;
+
+        self.mice
+            .iter()
+            .filter(|entry| entry.first.as_bytes().contains(first))
+            .filter(|entry| rest.ends_with(entry.suffix.as_bytes()))
+            .max_by_key(|entry| entry.suffix.len())
+            .map(|entry| &entry.device)
+    }
+}
+
+impl AprsPacket {
+    /// Looks up the device that sent the packet, by the comment for Mic-E
+    /// packets and by the destination otherwise
+    pub fn device<'a>(&self, table: &'a DeviceTable) -> Option<&'a Device> {
+        match &self.data {
+            // data type identifier, 3 bytes longitude, 3 bytes speed and
+            // course, symbol code and table
+            AprsData::Unknown(data) if matches!(data.first(), Some(b'`') | Some(b'\'')) => {
+                table.lookup_mice(data.get(9..)?)
+            }
+            _ => table.lookup_tocall(&self.to),
+        }
+    }
+}
+
+// number of literal characters if the pattern matches
+fn pattern_specificity(pattern: &str, tocall: &str) -> Option<usize> {
+    let (pattern, wildcard) = match pattern.strip_suffix('*') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    if tocall.len() < pattern.len() || (!wildcard && tocall.len() != pattern.len()) {
+        return None;
+    }
+
+    let mut specificity = 0;
+    for (p, c) in pattern.bytes().zip(tocall.bytes()) {
+        if p == c {
+            specificity += 1;
+        } else if p != b'?' {
+            return None;
+        }
+    }
+
+    Some(specificity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn model(device: Option<&Device>) -> Option<&str> {
+        device.map(|d| &d.model[..])
+    }
+
+    #[test]
+    fn lookup_tocall() {
+        let table = DeviceTable::embedded();
+        let lookup = |call| model(table.lookup_tocall(&Callsign::new(call, None)));
+
+        assert_eq!(lookup("APDR16"), Some("APRSdroid"));
+        assert_eq!(lookup("APK003"), Some("TH-D72"));
+        assert_eq!(lookup("APK002"), Some("TH-D7"));
+        assert_eq!(lookup("APX219"), Some("Xastir"));
+        assert_eq!(lookup("APDR1"), None);
+        assert_eq!(lookup("APDR161"), None);
+        assert_eq!(lookup("APRS"), None);
+
+        let device = table
+            .lookup_tocall(&Callsign::new("APY400", Some("2")))
+            .unwrap();
+        assert_eq!(device.vendor, "Yaesu");
+        assert_eq!(device.class, DeviceClass::Rig);
+    }
+
+    #[test]
+    fn lookup_mice() {
+        let table = DeviceTable::embedded();
+        let lookup = |comment: &[u8]| model(table.lookup_mice(comment));
+
+        assert_eq!(lookup(b">Hello"), Some("TH-D7A"));
+        assert_eq!(lookup(b">Hello="), Some("TH-D72"));
+        assert_eq!(lookup(b"]=Hello="), Some("TM-D710"));
+        assert_eq!(lookup(b"`Hello_ "), Some("VX-8"));
+        assert_eq!(lookup(b"'_0"), Some("FT3D"));
+        assert_eq!(lookup(b"`Hello|3"), Some("TinyTrak3"));
+        assert_eq!(lookup(b"`Hello"), None);
+        assert_eq!(lookup(b"Hello_ "), None);
+        assert_eq!(lookup(b""), None);
+    }
+
+    #[test]
+    fn packet_device() {
+        let packet = AprsPacket::try_from(&b"N0CALL>APDR16,WIDE1-1::DEST     :Hello"[..]).unwrap();
+
+        assert_eq!(
+            packet.device(&DeviceTable::embedded()),
+            Some(&Device {
+                vendor: "Open Source".to_owned(),
+                model: "APRSdroid".to_owned(),
+                class: DeviceClass::App,
+            })
+        );
+
+        let mice = AprsPacket::try_from(&br#"N0CALL>T2SP0W:`c8Zl Sj/]"4-}="#[..]).unwrap();
+        assert_eq!(
+            model(mice.device(&DeviceTable::embedded())),
+            Some("TM-D710")
+        );
+
+        let mice = AprsPacket::try_from(&b"N0CALL>T2SP0W:`c8Zl Sj/"[..]).unwrap();
+        assert_eq!(mice.device(&DeviceTable::embedded()), None);
+    }
+
+    #[test]
+    fn parse_table() {
+        let table = DeviceTable::parse(
+            "# comment\n\ntocall\tAPZ*\tExperimental\tAny\tballoon\nmice\t`\t_9\tYaesu\tFT9\tht\n",
+        )
+        .unwrap();
+
+        let device = table.lookup_tocall(&Callsign::new("APZ123", None)).unwrap();
+        assert_eq!(device.class, DeviceClass::Other("balloon".to_owned()));
+        assert_eq!(model(table.lookup_mice(b"`_9")), Some("FT9"));
+        assert_eq!(model(table.lookup_mice(b"'_9")), None);
+
+        assert!(matches!(
+            DeviceTable::parse("\ntocall\tAPDR??\tOpen Source\tAPRSdroid\n"),
+            Err(DeviceTableError::InvalidLine { line: 2, content })
+                if content == "tocall\tAPDR??\tOpen Source\tAPRSdroid"
+        ));
+        assert!(DeviceTable::parse("foo\tbar\n").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn load_table() {
+        let path = std::env::temp_dir().join(format!(
+            "aprs-parser-devices-{}-load-table.tsv",
+            std::process::id()
+        ));
+        std::fs::write(&path, "tocall\tAPZ001\tTest\tTest\tapp\n").unwrap();
+
+        let table = DeviceTable::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            model(table.lookup_tocall(&Callsign::new("APZ001", None))),
+            Some("Test")
+        );
+
+        assert!(matches!(
+            DeviceTable::load(&path),
+            Err(DeviceTableError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,314 @@
+// duplicate detection
+//
+// Igates and digipeaters drop packets they have already handled within a
+// short window. Two packets are duplicates if source, destination and
+// information field match; the path is ignored since it changes on every
+// hop. Only a 64 bit FNV-1a hash of these parts is kept per packet. The
+// information field is hashed as received, so packets that only differ in
+// details the parser doesn't interpret are still told apart.
+
+use alloc::collections::{BTreeSet, VecDeque};
+use core::time::Duration;
+
+use AprsPacket;
+use Callsign;
+use Clock;
+use EncodeError;
+use EncodeWrite;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DupeStats {
+    /// Packets reported as duplicates
+    pub hits: u64,
+    /// Packets seen for the first time
+    pub misses: u64,
+    /// Packets forgotten before the end of the window to stay within the
+    /// capacity
+    pub evictions: u64,
+}
+
+/// Remembers recent packets to detect duplicates
+///
+/// ```rust
+/// # extern crate aprs_parser;
+/// # use std::convert::TryFrom;
+/// # use std::time::Duration;
+/// # use aprs_parser::{AprsPacket, DupeChecker, ManualClock};
+/// let clock = ManualClock::new();
+/// let mut dupes = DupeChecker::new(&clock);
+///
+/// let packet = AprsPacket::try_from(&b"N0CALL>APRS,WIDE1-1:>status"[..]).unwrap();
+/// let digipeated = AprsPacket::try_from(&b"N0CALL>APRS,DIGI*,WIDE2-1:>status"[..]).unwrap();
+/// assert!(!dupes.is_duplicate(&packet).unwrap());
+/// assert!(dupes.is_duplicate(&digipeated).unwrap());
+///
+/// clock.advance(Duration::from_secs(30));
+/// assert!(!dupes.is_duplicate(&packet).unwrap());
+/// assert!(dupes.is_duplicate_raw(&packet.from, &packet.to, b">status"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DupeChecker<C> {
+    clock: C,
+    window: Duration,
+    capacity: usize,
+    // hashes within the window, and the same with the time they were first
+    // seen in time order
+    seen: BTreeSet<u64>,
+    order: VecDeque<(Duration, u64)>,
+    stats: DupeStats,
+}
+
+impl<C: Clock> DupeChecker<C> {
+    /// Uses a window of 30 seconds and remembers up to 10000 packets
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            window: Duration::from_secs(30),
+            capacity: 10_000,
+            seen: BTreeSet::new(),
+            order: VecDeque::new(),
+            stats: DupeStats::default(),
+        }
+    }
+
+    /// How long after the first copy of a packet others count as duplicates
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Maximum number of packets remembered, the oldest are forgotten first
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Checks a packet and remembers it if it hasn't been seen within the
+    /// window. Copies don't extend the window.
+    ///
+    /// The information field is re-encoded, which gives back the received
+    /// bytes for parsed packets. Fails without remembering anything if the
+    /// packet can't be encoded.
+    pub fn is_duplicate(&mut self, packet: &AprsPacket) -> Result<bool, EncodeError> {
+        let mut hasher = header_hasher(&packet.from, &packet.to);
+        packet.data.encode(&mut hasher)?;
+
+        Ok(self.check(hasher.0))
+    }
+
+    /// Like [`DupeChecker::is_duplicate`], for the raw information field
+    /// after the `:` of a packet
+    pub fn is_duplicate_raw(&mut self, from: &Callsign, to: &Callsign, info: &[u8]) -> bool {
+        let mut hasher = header_hasher(from, to);
+        // the hasher can't fail
+        let _ = hasher.write_all(info);
+
+        self.check(hasher.0)
+    }
+
+    fn check(&mut self, hash: u64) -> bool {
+        let now = self.clock.now();
+        self.expire(now);
+
+        if self.seen.contains(&hash) {
+            self.stats.hits += 1;
+            return true;
+        }
+
+        if self.seen.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+        }
+
+        self.seen.insert(hash);
+        self.order.push_back((now, hash));
+        self.stats.misses += 1;
+        false
+    }
+
+    pub fn stats(&self) -> DupeStats {
+        self.stats
+    }
+
+    /// Number of packets currently remembered
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Forgets all packets, keeping the statistics
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+    }
+
+    fn expire(&mut self, now: Duration) {
+        while let Some((at, hash)) = self.order.front() {
+            if now < *at + self.window {
+                break;
+            }
+            self.seen.remove(hash);
+            self.order.pop_front();
+        }
+    }
+}
+
+// source and destination, like the start of the encoded packet without its
+// path. The information field is appended by the caller.
+fn header_hasher(from: &Callsign, to: &Callsign) -> Fnv1a {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    // the hasher can't fail
+    let _ = write!(hasher, "{}>{}:", from, to);
+    hasher
+}
+
+struct Fnv1a(u64);
+
+impl EncodeWrite for Fnv1a {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), EncodeError> {
+        for b in buf {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+    use AprsData;
+    use ManualClock;
+
+    fn packet(b: &str) -> AprsPacket {
+        AprsPacket::try_from(b.as_bytes()).unwrap()
+    }
+
+    fn dupe_hash(packet: &AprsPacket) -> u64 {
+        let mut hasher = header_hasher(&packet.from, &packet.to);
+        packet.data.encode(&mut hasher).unwrap();
+        hasher.0
+    }
+
+    #[test]
+    fn hash_ignores_path() {
+        assert_eq!(
+            dupe_hash(&packet("N0CALL>APRS,WIDE1-1,WIDE2-1::DEST     :Hello{1")),
+            dupe_hash(&packet("N0CALL>APRS,qAR,IGATE::DEST     :Hello{1"))
+        );
+        assert_ne!(
+            dupe_hash(&packet("N0CALL>APRS:>status")),
+            dupe_hash(&packet("N0CALL-1>APRS:>status"))
+        );
+        assert_ne!(
+            dupe_hash(&packet("N0CALL>APRS:>status")),
+            dupe_hash(&packet("N0CALL>APZ001:>status"))
+        );
+        assert_ne!(
+            dupe_hash(&packet("N0CALL>APRS:>status")),
+            dupe_hash(&packet("N0CALL>APRS:>Status"))
+        );
+    }
+
+    #[test]
+    fn hash_keeps_raw_details() {
+        assert_ne!(
+            dupe_hash(&packet("N0CALL>APRS:/123456z4903.50N/07201.75W-")),
+            dupe_hash(&packet("N0CALL>APRS:/123456Z4903.50N/07201.75W-"))
+        );
+        assert_ne!(
+            dupe_hash(&packet("N0CALL>APRS:!/ABCD#$%^- >C")),
+            dupe_hash(&packet("N0CALL>APRS:!/ABCD#$%^- sT"))
+        );
+    }
+
+    #[test]
+    fn raw() {
+        let clock = ManualClock::new();
+        let mut dupes = DupeChecker::new(&clock);
+
+        let position = packet("N0CALL>APRS:!/ABCD#$%^- >C");
+        assert!(!dupes.is_duplicate_raw(&position.from, &position.to, b"!/ABCD#$%^- >C"));
+        assert!(dupes.is_duplicate(&position).unwrap());
+        assert!(!dupes.is_duplicate_raw(&position.from, &position.to, b"!/ABCD#$%^- sT"));
+    }
+
+    #[test]
+    fn unencodable() {
+        let clock = ManualClock::new();
+        let mut dupes = DupeChecker::new(&clock);
+
+        let mut message = packet("N0CALL>APRS::DEST     :Hello");
+        if let AprsData::Message(m) = &mut message.data {
+            m.addressee = b"TOOLONGADDR".to_vec();
+        }
+
+        assert!(dupes.is_duplicate(&message).is_err());
+        assert!(dupes.is_duplicate(&message).is_err());
+        assert!(dupes.is_empty());
+        assert_eq!(dupes.stats(), DupeStats::default());
+    }
+
+    #[test]
+    fn window() {
+        let clock = ManualClock::new();
+        let mut dupes = DupeChecker::new(&clock).window(Duration::from_secs(10));
+
+        assert!(!dupes
+            .is_duplicate(&packet("N0CALL>APRS:!4903.50N/07201.75W-"))
+            .unwrap());
+        clock.advance(Duration::from_secs(5));
+        assert!(!dupes.is_duplicate(&packet("N0CALL>APRS:>status")).unwrap());
+        assert!(dupes
+            .is_duplicate(&packet("N0CALL>APRS,WIDE1-1:!4903.50N/07201.75W-"))
+            .unwrap());
+        assert_eq!(dupes.len(), 2);
+
+        // copies don't extend the window
+        clock.advance(Duration::from_secs(5));
+        assert!(!dupes
+            .is_duplicate(&packet("N0CALL>APRS:!4903.50N/07201.75W-"))
+            .unwrap());
+        assert!(dupes.is_duplicate(&packet("N0CALL>APRS:>status")).unwrap());
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(dupes.len(), 2);
+        assert!(!dupes.is_duplicate(&packet("N0CALL>APRS:>status")).unwrap());
+
+        assert_eq!(
+            dupes.stats(),
+            DupeStats {
+                hits: 2,
+                misses: 4,
+                evictions: 0,
+            }
+        );
+
+        dupes.clear();
+        assert!(dupes.is_empty());
+        assert!(!dupes.is_duplicate(&packet("N0CALL>APRS:>status")).unwrap());
+    }
+
+    #[test]
+    fn capacity() {
+        let clock = ManualClock::new();
+        let mut dupes = DupeChecker::new(&clock).capacity(2);
+
+        assert!(!dupes.is_duplicate(&packet("A>APRS:>1")).unwrap());
+        assert!(!dupes.is_duplicate(&packet("A>APRS:>2")).unwrap());
+        assert!(!dupes.is_duplicate(&packet("A>APRS:>3")).unwrap());
+        assert_eq!(dupes.len(), 2);
+
+        assert!(dupes.is_duplicate(&packet("A>APRS:>3")).unwrap());
+        assert!(dupes.is_duplicate(&packet("A>APRS:>2")).unwrap());
+        assert!(!dupes.is_duplicate(&packet("A>APRS:>1")).unwrap());
+        assert_eq!(dupes.stats().evictions, 2);
+    }
+}
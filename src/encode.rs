@@ -0,0 +1,177 @@
+// output abstraction for all encoders
+//
+// With the `std` feature every `std::io::Write` can be used directly.
+// Without it, packets can be encoded into a `Vec<u8>` or, wrapped in
+// `FmtWriter`, into any `core::fmt::Write`. `SliceWriter` and
+// `CountingWriter` back the allocation free `AprsPacket::encode_to_slice` and
+// `AprsPacket::encoded_len`.
+
+use core::fmt;
+
+use EncodeError;
+
+/// Byte sink the encoders write to
+pub trait EncodeWrite {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), EncodeError>;
+
+    /// Makes `write!` work on encoders
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), EncodeError> {
+        struct Adapter<'a, T: ?Sized> {
+            inner: &'a mut T,
+            error: Option<EncodeError>,
+        }
+
+        impl<'a, T: EncodeWrite + ?Sized> fmt::Write for Adapter<'a, T> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.inner.write_all(s.as_bytes()).map_err(|e| {
+                    self.error = Some(e);
+                    fmt::Error
+                })
+            }
+        }
+
+        let mut adapter = Adapter {
+            inner: self,
+            error: None,
+        };
+
+        fmt::write(&mut adapter, args)
+            .map_err(|_| adapter.error.take().unwrap_or(EncodeError::InvalidData))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write + ?Sized> EncodeWrite for W {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), EncodeError> {
+        std::io::Write::write_all(self, buf)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl EncodeWrite for alloc::vec::Vec<u8> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), EncodeError> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: EncodeWrite + ?Sized> EncodeWrite for &mut W {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), EncodeError> {
+        (**self).write_all(buf)
+    }
+}
+
+/// Encodes into a `core::fmt::Write`, e.g. a `String` or a fixed capacity
+/// string on embedded targets. Fails on non-UTF8 comments and messages.
+pub struct FmtWriter<W>(pub W);
+
+impl<W: fmt::Write> EncodeWrite for FmtWriter<W> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), EncodeError> {
+        let s = core::str::from_utf8(buf).map_err(|_| EncodeError::NonUtf8(buf.to_vec()))?;
+        self.0.write_str(s).map_err(|_| EncodeError::InvalidData)
+    }
+}
+
+// Writes into a fixed buffer. Once it is full, only the length is tracked so
+// the caller can report the size that would have been needed.
+pub(crate) struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    pub(crate) fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    pub(crate) fn finish(self) -> Result<usize, EncodeError> {
+        if self.len > self.buf.len() {
+            return Err(EncodeError::BufferTooSmall(self.len));
+        }
+
+        Ok(self.len)
+    }
+}
+
+impl<'a> EncodeWrite for SliceWriter<'a> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), EncodeError> {
+        let end = self.len + buf.len();
+        if let Some(dest) = self.buf.get_mut(self.len..end) {
+            dest.copy_from_slice(buf);
+        }
+        self.len = end;
+
+        Ok(())
+    }
+}
+
+// Discards the output and counts its length
+pub(crate) struct CountingWriter(pub(crate) usize);
+
+impl EncodeWrite for CountingWriter {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), EncodeError> {
+        self.0 += buf.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use core::convert::TryFrom;
+    use AprsPacket;
+
+    #[test]
+    fn encode_to_fmt_writer() {
+        let original = &b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :Hello World! {3a2B975"[..];
+
+        let mut buf = FmtWriter(String::new());
+        AprsPacket::try_from(original)
+            .unwrap()
+            .encode(&mut buf)
+            .unwrap();
+        assert_eq!(buf.0.as_bytes(), original);
+    }
+
+    #[test]
+    fn encode_non_utf8_to_fmt_writer() {
+        let original = &b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :\xc3\x28"[..];
+
+        let mut buf = FmtWriter(String::new());
+        assert!(matches!(
+            AprsPacket::try_from(original).unwrap().encode(&mut buf),
+            Err(EncodeError::NonUtf8(x)) if x == b"\xc3\x28"
+        ));
+    }
+
+    #[test]
+    fn slice_writer() {
+        let mut buf = [0; 8];
+        let mut writer = SliceWriter::new(&mut buf);
+        writer.write_all(b"abc").unwrap();
+        write!(writer, "{:03}", 7).unwrap();
+        assert_eq!(writer.finish().unwrap(), 6);
+        assert_eq!(&buf[..6], b"abc007");
+
+        let mut writer = SliceWriter::new(&mut buf);
+        writer.write_all(b"abcdef").unwrap();
+        writer.write_all(b"ghi").unwrap();
+        writer.write_all(b"j").unwrap();
+        assert!(matches!(
+            writer.finish(),
+            Err(EncodeError::BufferTooSmall(10))
+        ));
+        assert_eq!(&buf[..6], b"abcdef");
+    }
+
+    #[test]
+    fn counting_writer() {
+        let mut writer = CountingWriter(0);
+        writer.write_all(b"abc").unwrap();
+        write!(writer, "{:03}", 7).unwrap();
+        assert_eq!(writer.0, 6);
+    }
+}
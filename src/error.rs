@@ -1,43 +1,434 @@
-#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use Precision;
+use Timestamp;
+use TimestampField;
+
+#[derive(Debug, Eq, PartialEq)]
 pub enum AprsError {
-    #[error("Non-UTF8 Callsign: {0:?}")]
     NonUtf8Callsign(Vec<u8>),
-    #[error("Empty Callsign: {0:?}")]
     EmptyCallsign(String),
-    #[error("Empty Callsign SSID: {0:?}")]
     EmptySSID(String),
-    #[error("Invalid Timestamp: {0:?}")]
     InvalidTimestamp(Vec<u8>),
-    #[error("Unsupported Position Format: {0:?}")]
+    TimestampOutOfRange { field: TimestampField, value: u8 },
     UnsupportedPositionFormat(Vec<u8>),
-    #[error("Invalid Position: {0:?}")]
     InvalidPosition(Vec<u8>),
-    #[error("Invalid Latitude: {0:?}")]
     InvalidLatitude(Vec<u8>),
-    #[error("Invalid Longitude: {0:?}")]
     InvalidLongitude(Vec<u8>),
-    #[error("Invalid Packet: {0:?}")]
     InvalidPacket(Vec<u8>),
-    #[error("Invalid Message Destination: {0:?}")]
     InvalidMessageDestination(Vec<u8>),
-    #[error("Invalid Message ID: {0:?}")]
     InvalidMessageId(Vec<u8>),
-    #[error("Invalid Compressed cs: {0:?}")]
     InvalidCs([u8; 2]),
+    InvalidUserDefined(Vec<u8>),
+    InvalidAx25Frame(Vec<u8>),
+    LineTooLong(usize),
+}
+
+impl Display for AprsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NonUtf8Callsign(x) => write!(f, "Non-UTF8 Callsign: {:?}", x),
+            Self::EmptyCallsign(x) => write!(f, "Empty Callsign: {:?}", x),
+            Self::EmptySSID(x) => write!(f, "Empty Callsign SSID: {:?}", x),
+            Self::InvalidTimestamp(x) => write!(f, "Invalid Timestamp: {:?}", x),
+            Self::TimestampOutOfRange { field, value } => {
+                write!(f, "Timestamp {:?} out of range: {}", field, value)
+            }
+            Self::UnsupportedPositionFormat(x) => {
+                write!(f, "Unsupported Position Format: {:?}", x)
+            }
+            Self::InvalidPosition(x) => write!(f, "Invalid Position: {:?}", x),
+            Self::InvalidLatitude(x) => write!(f, "Invalid Latitude: {:?}", x),
+            Self::InvalidLongitude(x) => write!(f, "Invalid Longitude: {:?}", x),
+            Self::InvalidPacket(x) => write!(f, "Invalid Packet: {:?}", x),
+            Self::InvalidMessageDestination(x) => {
+                write!(f, "Invalid Message Destination: {:?}", x)
+            }
+            Self::InvalidMessageId(x) => write!(f, "Invalid Message ID: {:?}", x),
+            Self::InvalidCs(x) => write!(f, "Invalid Compressed cs: {:?}", x),
+            Self::InvalidUserDefined(x) => write!(f, "Invalid User-Defined Data: {:?}", x),
+            Self::InvalidAx25Frame(x) => write!(f, "Invalid AX.25 Frame: {:?}", x),
+            Self::LineTooLong(x) => write!(f, "Line too long: {} bytes", x),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AprsError {}
+
+/// Part of a packet that a [`ParseWarning`] or [`ParseError`] refers to
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ParseField {
+    Header,
+    From,
+    To,
+    Via,
+    Timestamp,
+    Position,
+    Latitude,
+    Longitude,
+    CompressedCs,
+    Message,
+}
+
+impl ParseField {
+    /// What the parser expects in this field
+    pub fn expected(self) -> &'static str {
+        match self {
+            Self::Header => "a header like N0CALL>APRS,WIDE1-1: before the data",
+            Self::From | Self::To | Self::Via => "a callsign with an optional SSID like N0CALL-7",
+            Self::Timestamp => "a timestamp like 092345z, 092345/ or 234517h",
+            Self::Position => "an uncompressed or compressed position with a symbol",
+            Self::Latitude => "a latitude like 4903.50N or 4 base91 digits",
+            Self::Longitude => "a longitude like 07201.75W or 4 base91 digits",
+            Self::CompressedCs => "a compressed course/speed, radio range or altitude and type",
+            Self::Message => "a message like :ADDRESSEE:text{id",
+        }
+    }
+}
+
+/// A malformed field that lenient parsing skipped or replaced
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseWarning {
+    pub field: ParseField,
+    /// Offset of the field from the start of the packet
+    pub offset: usize,
+    /// Length of the field in bytes
+    pub len: usize,
+    pub reason: AprsError,
+}
+
+impl ParseWarning {
+    pub(crate) fn new(field: ParseField, offset: usize, len: usize, reason: AprsError) -> Self {
+        Self {
+            field,
+            offset,
+            len,
+            reason,
+        }
+    }
+
+    // lenient parsing records the warning and goes on, strict parsing fails
+    pub(crate) fn recover(
+        self,
+        warnings: Option<&mut Vec<ParseWarning>>,
+    ) -> Result<(), ParseWarning> {
+        match warnings {
+            Some(warnings) => {
+                warnings.push(self);
+                Ok(())
+            }
+            None => Err(self),
+        }
+    }
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:?} at byte {}: {}",
+            self.field, self.offset, self.reason
+        )
+    }
+}
+
+/// A parse error located in the input
+///
+/// `Display` shows the input with a caret line under the bad bytes:
+///
+/// ```text
+/// Invalid Latitude: [52, 120, 50, 49, 46, 54, 49, 78]
+/// expected a latitude like 4903.50N or 4 base91 digits, found "4x21.61N"
+///   N0CALL>APRS:!4x21.61N\01224.49E^
+///                ^^^^^^^^
+/// ```
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub field: ParseField,
+    /// Offset of the field from the start of the input
+    pub offset: usize,
+    /// Length of the field in bytes
+    pub len: usize,
+    pub reason: AprsError,
+    /// The whole input
+    pub input: Vec<u8>,
+}
+
+impl ParseError {
+    pub(crate) fn new(warning: ParseWarning, input: &[u8]) -> Self {
+        Self {
+            field: warning.field,
+            offset: warning.offset,
+            len: warning.len,
+            reason: warning.reason,
+            input: input.to_vec(),
+        }
+    }
+
+    pub fn expected(&self) -> &'static str {
+        self.field.expected()
+    }
+
+    /// The bad bytes
+    pub fn found(&self) -> &[u8] {
+        let start = self.offset.min(self.input.len());
+        let end = (self.offset + self.len).min(self.input.len());
+        &self.input[start..end]
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "{}", self.reason)?;
+        writeln!(
+            f,
+            "expected {}, found {:?}",
+            self.expected(),
+            String::from_utf8_lossy(self.found())
+        )?;
+
+        // one character per byte keeps the caret aligned
+        write!(f, "  ")?;
+        for b in &self.input {
+            let c = if b.is_ascii_graphic() || *b == b' ' {
+                *b as char
+            } else {
+                '.'
+            };
+            write!(f, "{}", c)?;
+        }
+        writeln!(f)?;
+
+        write!(f, "  ")?;
+        for _ in 0..self.offset {
+            write!(f, " ")?;
+        }
+        for _ in 0..self.len.max(1) {
+            write!(f, "^")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.reason)
+    }
+}
+
+impl From<ParseError> for AprsError {
+    fn from(e: ParseError) -> Self {
+        e.reason
+    }
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug)]
 pub enum EncodeError {
-    #[error("Invalid Latitude: {0}")]
     InvalidLatitude(f64),
-    #[error("Invalid Longitude: {0}")]
     InvalidLongitude(f64),
-    #[error("Invalid Aprs Data")]
     InvalidData,
-    #[error("Invalid Message Addressee: {0:?}")]
     InvalidMessageAddressee(Vec<u8>),
-    #[error("Compressed altitude requires the nmea source to be gga")]
+    InvalidMessageText(Vec<u8>),
+    /// Longer than [`MAX_MESSAGE_TEXT_LENGTH`](crate::MAX_MESSAGE_TEXT_LENGTH),
+    /// the length is attached
+    MessageTextTooLong(usize),
     NonGgaAltitude,
-    #[error(transparent)]
-    Write(#[from] std::io::Error),
+    InvalidAx25Callsign(String),
+    TooManyDigipeaters(usize),
+    InvalidKissPort(u8),
+    InvalidKissCommand(u8),
+    InvalidCallsign(String),
+    InvalidCourse(u16),
+    InvalidSpeed(f64),
+    InvalidAltitude(f64),
+    InvalidComment(Vec<u8>),
+    InvalidTimestamp(Timestamp),
+    /// The position format can't carry the precision of a coordinate, e.g.
+    /// compressed coordinates in an uncompressed position
+    UnsupportedPrecision(Precision),
+    /// The output buffer is too small, the encoded length is attached
+    BufferTooSmall(usize),
+    /// Raw bytes can not be written to a `core::fmt::Write`
+    NonUtf8(Vec<u8>),
+    #[cfg(feature = "std")]
+    Write(std::io::Error),
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLatitude(x) => write!(f, "Invalid Latitude: {}", x),
+            Self::InvalidLongitude(x) => write!(f, "Invalid Longitude: {}", x),
+            Self::InvalidData => write!(f, "Invalid Aprs Data"),
+            Self::InvalidMessageAddressee(x) => write!(f, "Invalid Message Addressee: {:?}", x),
+            Self::InvalidMessageText(x) => write!(f, "Invalid Message Text: {:?}", x),
+            Self::MessageTextTooLong(x) => write!(f, "Message text too long: {} bytes", x),
+            Self::NonGgaAltitude => {
+                write!(f, "Compressed altitude requires the nmea source to be gga")
+            }
+            Self::InvalidAx25Callsign(x) => {
+                write!(f, "Callsign can not be represented in AX.25: {}", x)
+            }
+            Self::TooManyDigipeaters(x) => write!(f, "Too many digipeaters for AX.25: {}", x),
+            Self::InvalidKissPort(x) => write!(f, "Invalid KISS Port: {}", x),
+            Self::InvalidKissCommand(x) => write!(f, "Invalid KISS Command: {}", x),
+            Self::InvalidCallsign(x) => write!(f, "Invalid Callsign: {:?}", x),
+            Self::InvalidCourse(x) => write!(f, "Invalid Course: {}", x),
+            Self::InvalidSpeed(x) => write!(f, "Invalid Speed: {}", x),
+            Self::InvalidAltitude(x) => write!(f, "Invalid Altitude: {}", x),
+            Self::InvalidComment(x) => write!(f, "Invalid Comment: {:?}", x),
+            Self::InvalidTimestamp(x) => write!(f, "Invalid Timestamp: {:?}", x),
+            Self::UnsupportedPrecision(x) => {
+                write!(f, "Precision not supported by the position format: {:?}", x)
+            }
+            Self::BufferTooSmall(x) => write!(f, "Buffer too small, {} bytes needed", x),
+            Self::NonUtf8(x) => write!(f, "Non-UTF8 Data: {:?}", x),
+            #[cfg(feature = "std")]
+            Self::Write(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            // transparent, like `Display`
+            Self::Write(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for EncodeError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Write(e)
+    }
+}
+
+#[derive(Debug)]
+pub enum DeviceTableError {
+    InvalidLine {
+        line: usize,
+        content: String,
+    },
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl Display for DeviceTableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLine { line, content } => {
+                write!(f, "Invalid Device Table Line {}: {:?}", line, content)
+            }
+            #[cfg(feature = "std")]
+            Self::Io(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeviceTableError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for DeviceTableError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum FilterError {
+    UnknownFilter(String),
+    MissingArguments(String),
+    InvalidArgument { filter: String, argument: String },
+    InvalidPacketType(char),
+}
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownFilter(x) => write!(f, "Unknown Filter: {:?}", x),
+            Self::MissingArguments(x) => write!(f, "Missing Filter Arguments: {:?}", x),
+            Self::InvalidArgument { filter, argument } => {
+                write!(f, "Invalid Filter Argument {:?} in {:?}", argument, filter)
+            }
+            Self::InvalidPacketType(x) => write!(f, "Invalid Packet Type: {:?}", x),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FilterError {}
+
+/// Error of [`AprsIsConnection::login`](crate::AprsIsConnection::login)
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum LoginError {
+    /// Reading from or writing to the stream failed
+    Io(std::io::Error),
+    /// The login line can not be encoded
+    Encode(EncodeError),
+    /// The server closed the connection after this comment instead of
+    /// confirming the login, e.g. `Invalid username format`
+    Rejected(String),
+    /// The server closed the connection without a response
+    ConnectionClosed,
+    /// A `logresp` comment that can not be parsed
+    InvalidResponse(String),
+}
+
+#[cfg(feature = "std")]
+impl Display for LoginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => Display::fmt(e, f),
+            Self::Encode(e) => Display::fmt(e, f),
+            Self::Rejected(x) => write!(f, "Login Rejected: {:?}", x),
+            Self::ConnectionClosed => write!(f, "Connection Closed During Login"),
+            Self::InvalidResponse(x) => write!(f, "Invalid Login Response: {:?}", x),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LoginError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => e.source(),
+            Self::Encode(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for LoginError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<EncodeError> for LoginError {
+    fn from(e: EncodeError) -> Self {
+        match e {
+            EncodeError::Write(e) => Self::Io(e),
+            e => Self::Encode(e),
+        }
+    }
 }
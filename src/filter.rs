@@ -0,0 +1,766 @@
+// APRS-IS server-side filters
+//
+// A filter consists of space separated parts like `r/48.1/11.5/50` or
+// `-t/w`. A packet passes if at least one inclusive part matches and no
+// exclusive (`-` prefixed) part matches.
+//
+// See <http://www.aprs-is.net/javAPRSFilter.aspx> for the syntax.
+
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use geo::GeoPoint;
+use AprsData;
+use AprsPacket;
+use Callsign;
+use FilterError;
+use Latitude;
+use Longitude;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum PacketType {
+    Position,
+    Object,
+    Item,
+    Message,
+    Query,
+    Status,
+    Telemetry,
+    UserDefined,
+    Nws,
+    Weather,
+}
+
+impl PacketType {
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            'p' => PacketType::Position,
+            'o' => PacketType::Object,
+            'i' => PacketType::Item,
+            'm' => PacketType::Message,
+            'q' => PacketType::Query,
+            's' => PacketType::Status,
+            't' => PacketType::Telemetry,
+            'u' => PacketType::UserDefined,
+            'n' => PacketType::Nws,
+            'w' => PacketType::Weather,
+            _ => return None,
+        })
+    }
+
+    // for unsupported data, by its data type identifier
+    fn from_identifier(b: u8) -> Option<Self> {
+        Some(match b {
+            b'`' | b'\'' | b'$' => PacketType::Position,
+            b';' => PacketType::Object,
+            b')' => PacketType::Item,
+            b'?' => PacketType::Query,
+            b'>' => PacketType::Status,
+            b'T' => PacketType::Telemetry,
+            b'_' => PacketType::Weather,
+            _ => return None,
+        })
+    }
+
+    fn matches(self, packet: &AprsPacket) -> bool {
+        match (self, &packet.data) {
+            (PacketType::Position, AprsData::Position(_)) => true,
+            (PacketType::Weather, AprsData::Position(p)) => p.symbol_code == '_',
+            (PacketType::Message, AprsData::Message(_)) => true,
+            (PacketType::Query, AprsData::Message(m)) => m.text.starts_with(b"?"),
+            (PacketType::Telemetry, AprsData::Message(m)) => {
+                [&b"PARM."[..], &b"UNIT."[..], &b"EQNS."[..], &b"BITS."[..]]
+                    .iter()
+                    .any(|prefix| m.text.starts_with(prefix))
+            }
+            (PacketType::Nws, AprsData::Message(m)) => m.addressee.starts_with(b"NWS"),
+            (PacketType::UserDefined, AprsData::UserDefined(_)) => true,
+            (packet_type, AprsData::Unknown(data)) => {
+                data.first().and_then(|b| PacketType::from_identifier(*b)) == Some(packet_type)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum FilterKind {
+    /// `r/lat/lon/dist`
+    Range {
+        latitude: Latitude,
+        longitude: Longitude,
+        distance_km: f64,
+    },
+    /// `p/aa/bb/cc`
+    Prefix(Vec<String>),
+    /// `b/call1/call2`, wildcards allowed
+    Budlist(Vec<String>),
+    /// `o/obj1/obj2`, wildcards allowed
+    Object(Vec<String>),
+    /// `t/poimqstunw` or `t/poimqstunw/call/dist`
+    Type {
+        types: Vec<PacketType>,
+        range: Option<(String, f64)>,
+    },
+    /// `s/pri/alt/overlay`
+    Symbol {
+        primary: String,
+        alternate: String,
+        overlay: String,
+    },
+    /// `d/digi1/digi2`, wildcards allowed
+    Digipeater(Vec<String>),
+    /// `a/latN/lonW/latS/lonE`
+    Area {
+        north: f64,
+        west: f64,
+        south: f64,
+        east: f64,
+    },
+    /// `e/call1/call2`, wildcards allowed
+    Entry(Vec<String>),
+    /// `g/call1/call2`, wildcards allowed
+    Group(Vec<String>),
+    /// `q/con/I`
+    QConstruct { constructs: String, igate: bool },
+    /// `m/dist`
+    MyRange(f64),
+    /// `f/call/dist`
+    FriendRange { callsign: String, distance_km: f64 },
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct FilterPart {
+    pub exclude: bool,
+    pub kind: FilterKind,
+}
+
+/// Positions the filter evaluation can not know from the packet alone
+pub trait FilterContext {
+    /// Position of the station the filter belongs to, used by `m/`
+    fn own_position(&self) -> Option<(Latitude, Longitude)> {
+        None
+    }
+
+    /// Last known position of another station, used by `f/` and `t/`
+    fn station_position(&self, _callsign: &str) -> Option<(Latitude, Longitude)> {
+        None
+    }
+}
+
+impl FilterContext for () {}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Filter {
+    pub parts: Vec<FilterPart>,
+}
+
+impl Filter {
+    /// Checks the packet against the filter without any station positions,
+    /// i.e. `m/`, `f/` and ranged `t/` parts never match.
+    pub fn matches(&self, packet: &AprsPacket) -> bool {
+        self.matches_with(packet, &())
+    }
+
+    pub fn matches_with<C: FilterContext + ?Sized>(
+        &self,
+        packet: &AprsPacket,
+        context: &C,
+    ) -> bool {
+        let mut included = false;
+
+        for part in &self.parts {
+            if part.kind.matches(packet, context) {
+                if part.exclude {
+                    return false;
+                }
+                included = true;
+            }
+        }
+
+        included
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split_whitespace()
+            .map(FilterPart::from_str)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { parts })
+    }
+}
+
+impl FromStr for FilterPart {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (exclude, rest) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+
+        let mut args = rest.split('/');
+        let kind = args.next().unwrap_or("");
+        let args: Vec<&str> = args.collect();
+
+        let missing = || FilterError::MissingArguments(s.to_owned());
+        let number = |arg: &str| {
+            arg.parse::<f64>()
+                .ok()
+                .filter(|x| x.is_finite())
+                .ok_or_else(|| FilterError::InvalidArgument {
+                    filter: s.to_owned(),
+                    argument: arg.to_owned(),
+                })
+        };
+        let list = || {
+            if args.is_empty() || args.iter().any(|a| a.is_empty()) {
+                Err(missing())
+            } else {
+                Ok(args.iter().map(|a| a.to_string()).collect())
+            }
+        };
+        let exact = |n: usize| {
+            if args.len() == n {
+                Ok(())
+            } else {
+                Err(missing())
+            }
+        };
+
+        let kind = match kind {
+            "r" => {
+                exact(3)?;
+                let latitude = Latitude::new(number(args[0])?).ok_or_else(|| {
+                    FilterError::InvalidArgument {
+                        filter: s.to_owned(),
+                        argument: args[0].to_owned(),
+                    }
+                })?;
+                let longitude = Longitude::new(number(args[1])?).ok_or_else(|| {
+                    FilterError::InvalidArgument {
+                        filter: s.to_owned(),
+                        argument: args[1].to_owned(),
+                    }
+                })?;
+                FilterKind::Range {
+                    latitude,
+                    longitude,
+                    distance_km: number(args[2])?,
+                }
+            }
+            "p" => FilterKind::Prefix(list()?),
+            "b" => FilterKind::Budlist(list()?),
+            "o" => FilterKind::Object(list()?),
+            "t" => {
+                if args.len() != 1 && args.len() != 3 {
+                    return Err(missing());
+                }
+                let types = args[0]
+                    .chars()
+                    .map(|c| PacketType::from_char(c).ok_or(FilterError::InvalidPacketType(c)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if types.is_empty() {
+                    return Err(missing());
+                }
+                let range = match args.len() {
+                    3 => Some((args[1].to_owned(), number(args[2])?)),
+                    _ => None,
+                };
+                FilterKind::Type { types, range }
+            }
+            "s" => {
+                if args.is_empty() || args.len() > 3 {
+                    return Err(missing());
+                }
+                FilterKind::Symbol {
+                    primary: args[0].to_owned(),
+                    alternate: args.get(1).unwrap_or(&"").to_string(),
+                    overlay: args.get(2).unwrap_or(&"").to_string(),
+                }
+            }
+            "d" => FilterKind::Digipeater(list()?),
+            "a" => {
+                exact(4)?;
+                FilterKind::Area {
+                    north: number(args[0])?,
+                    west: number(args[1])?,
+                    south: number(args[2])?,
+                    east: number(args[3])?,
+                }
+            }
+            "e" => FilterKind::Entry(list()?),
+            "g" => FilterKind::Group(list()?),
+            "q" => {
+                if args.is_empty() || args.len() > 2 {
+                    return Err(missing());
+                }
+                let igate = match args.get(1) {
+                    None => false,
+                    Some(&"I") => true,
+                    Some(arg) => {
+                        return Err(FilterError::InvalidArgument {
+                            filter: s.to_owned(),
+                            argument: arg.to_string(),
+                        })
+                    }
+                };
+                FilterKind::QConstruct {
+                    constructs: args[0].to_owned(),
+                    igate,
+                }
+            }
+            "m" => {
+                exact(1)?;
+                FilterKind::MyRange(number(args[0])?)
+            }
+            "f" => {
+                exact(2)?;
+                FilterKind::FriendRange {
+                    callsign: args[0].to_owned(),
+                    distance_km: number(args[1])?,
+                }
+            }
+            _ => return Err(FilterError::UnknownFilter(s.to_owned())),
+        };
+
+        Ok(Self { exclude, kind })
+    }
+}
+
+impl FilterKind {
+    fn matches<C: FilterContext + ?Sized>(&self, packet: &AprsPacket, context: &C) -> bool {
+        let position = match &packet.data {
+            AprsData::Position(p) => Some((p.latitude, p.longitude)),
+            _ => None,
+        };
+        let within =
+            |center: Option<(Latitude, Longitude)>, distance_km: f64| match (center, position) {
+                (Some(center), Some(position)) => {
+                    let distance = GeoPoint::new(center.0, center.1)
+                        .distance(&GeoPoint::new(position.0, position.1));
+                    distance.kilometers() <= distance_km
+                }
+                _ => false,
+            };
+
+        match self {
+            FilterKind::Range {
+                latitude,
+                longitude,
+                distance_km,
+            } => within(Some((*latitude, *longitude)), *distance_km),
+            FilterKind::Prefix(prefixes) => {
+                let from = packet.from.to_string();
+                prefixes.iter().any(|p| {
+                    from.to_ascii_uppercase()
+                        .starts_with(&p.to_ascii_uppercase())
+                })
+            }
+            FilterKind::Budlist(calls) => matches_any(calls, &packet.from),
+            FilterKind::Object(names) => {
+                object_name(packet).map_or(false, |name| matches_any_str(names, &name))
+            }
+            FilterKind::Type { types, range } => {
+                types.iter().any(|t| t.matches(packet))
+                    && match range {
+                        Some((call, distance_km)) => {
+                            within(context.station_position(call), *distance_km)
+                        }
+                        None => true,
+                    }
+            }
+            FilterKind::Symbol {
+                primary,
+                alternate,
+                overlay,
+            } => match &packet.data {
+                AprsData::Position(p) if p.symbol_table == '/' => primary.contains(p.symbol_code),
+                AprsData::Position(p) => {
+                    alternate.contains(p.symbol_code)
+                        && (overlay.is_empty() || overlay.contains(p.symbol_table))
+                }
+                _ => false,
+            },
+            FilterKind::Digipeater(calls) => {
+                // everything up to the last digipeater marked with `*` has been used
+                let used = packet
+                    .via
+                    .iter()
+                    .rposition(|v| v.to_string().ends_with('*'))
+                    .map_or(0, |i| i + 1);
+                packet.via[..used].iter().any(|v| {
+                    let call = v.to_string();
+                    matches_any_str(calls, call.trim_end_matches('*'))
+                })
+            }
+            FilterKind::Area {
+                north,
+                west,
+                south,
+                east,
+            } => match position {
+                Some((lat, lon)) => {
+                    *lat <= *north && *lat >= *south && *lon >= *west && *lon <= *east
+                }
+                None => false,
+            },
+            FilterKind::Entry(calls) => {
+                entry_station(packet).map_or(false, |entry| matches_any(calls, entry))
+            }
+            FilterKind::Group(calls) => match &packet.data {
+                AprsData::Message(m) => {
+                    matches_any_str(calls, &String::from_utf8_lossy(&m.addressee))
+                }
+                _ => false,
+            },
+            FilterKind::QConstruct { constructs, igate } => {
+                q_construct(packet).map_or(false, |q| {
+                    let construct = q.call[2..].chars().next();
+                    construct.map_or(false, |c| constructs.contains(c))
+                        // `I` additionally passes positions heard by igates
+                        || (*igate && q.call == "qAR" && position.is_some())
+                })
+            }
+            FilterKind::MyRange(distance_km) => within(context.own_position(), *distance_km),
+            FilterKind::FriendRange {
+                callsign,
+                distance_km,
+            } => within(context.station_position(callsign), *distance_km),
+        }
+    }
+}
+
+fn q_construct(packet: &AprsPacket) -> Option<&Callsign> {
+    packet
+        .via
+        .iter()
+        .find(|v| v.ssid.is_none() && v.call.len() == 3 && v.call.starts_with('q'))
+}
+
+/// The station that injected the packet into APRS-IS follows the q construct
+fn entry_station(packet: &AprsPacket) -> Option<&Callsign> {
+    let q = packet
+        .via
+        .iter()
+        .position(|v| v.ssid.is_none() && v.call.len() == 3 && v.call.starts_with('q'))?;
+    packet.via.get(q + 1)
+}
+
+/// Objects aren't decoded yet, the name is taken from the raw data
+fn object_name(packet: &AprsPacket) -> Option<String> {
+    match &packet.data {
+        AprsData::Unknown(data) if data.first() == Some(&b';') => {
+            let name = String::from_utf8_lossy(data.get(1..10)?);
+            Some(name.trim_end_matches(' ').to_owned())
+        }
+        _ => None,
+    }
+}
+
+fn matches_any(patterns: &[String], callsign: &Callsign) -> bool {
+    matches_any_str(patterns, &callsign.to_string())
+}
+
+fn matches_any_str(patterns: &[String], s: &str) -> bool {
+    patterns
+        .iter()
+        .any(|p| wildcard_match(p.as_bytes(), s.as_bytes()))
+}
+
+/// Case insensitive match supporting `*` and `?`
+fn wildcard_match(pattern: &[u8], s: &[u8]) -> bool {
+    let (mut p, mut i) = (0, 0);
+    // the last `*` and where in `s` its match currently ends
+    let mut star = None;
+
+    while i < s.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, i));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c.eq_ignore_ascii_case(&s[i]) => {
+                p += 1;
+                i += 1;
+            }
+            // let the last `*` match one more character and retry
+            _ => match star {
+                Some((star_p, star_i)) => {
+                    star = Some((star_p, star_i + 1));
+                    p = star_p + 1;
+                    i = star_i + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", part)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for FilterPart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
+        if self.exclude {
+            write!(f, "-")?;
+        }
+
+        match &self.kind {
+            FilterKind::Range {
+                latitude,
+                longitude,
+                distance_km,
+            } => write!(f, "r/{}/{}/{}", **latitude, **longitude, distance_km),
+            FilterKind::Prefix(l) => write!(f, "p/{}", l.join("/")),
+            FilterKind::Budlist(l) => write!(f, "b/{}", l.join("/")),
+            FilterKind::Object(l) => write!(f, "o/{}", l.join("/")),
+            FilterKind::Type { types, range } => {
+                write!(f, "t/")?;
+                for t in types {
+                    let c = match t {
+                        PacketType::Position => 'p',
+                        PacketType::Object => 'o',
+                        PacketType::Item => 'i',
+                        PacketType::Message => 'm',
+                        PacketType::Query => 'q',
+                        PacketType::Status => 's',
+                        PacketType::Telemetry => 't',
+                        PacketType::UserDefined => 'u',
+                        PacketType::Nws => 'n',
+                        PacketType::Weather => 'w',
+                    };
+                    write!(f, "{}", c)?;
+                }
+                if let Some((call, distance_km)) = range {
+                    write!(f, "/{}/{}", call, distance_km)?;
+                }
+                Ok(())
+            }
+            FilterKind::Symbol {
+                primary,
+                alternate,
+                overlay,
+            } => write!(f, "s/{}/{}/{}", primary, alternate, overlay),
+            FilterKind::Digipeater(l) => write!(f, "d/{}", l.join("/")),
+            FilterKind::Area {
+                north,
+                west,
+                south,
+                east,
+            } => write!(f, "a/{}/{}/{}/{}", north, west, south, east),
+            FilterKind::Entry(l) => write!(f, "e/{}", l.join("/")),
+            FilterKind::Group(l) => write!(f, "g/{}", l.join("/")),
+            FilterKind::QConstruct { constructs, igate } => {
+                write!(f, "q/{}", constructs)?;
+                if *igate {
+                    write!(f, "/I")?;
+                }
+                Ok(())
+            }
+            FilterKind::MyRange(distance_km) => write!(f, "m/{}", distance_km),
+            FilterKind::FriendRange {
+                callsign,
+                distance_km,
+            } => write!(f, "f/{}/{}", callsign, distance_km),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn packet(s: &str) -> AprsPacket {
+        AprsPacket::try_from(s.as_bytes()).unwrap()
+    }
+
+    fn position() -> AprsPacket {
+        packet(r"ICA3D17F2>APRS,WIDE1*,qAR,dl4mea:/074849h4821.61N\01224.49E^322/103/A=003054")
+    }
+
+    fn message() -> AprsPacket {
+        packet("N0CALL-5>APRS,qAC,T2TEST::DEST     :?APRSP")
+    }
+
+    fn matches(filter: &str, packet: &AprsPacket) -> bool {
+        Filter::from_str(filter).unwrap().matches(packet)
+    }
+
+    #[test]
+    fn range() {
+        assert!(matches("r/48.1/11.6/100", &position()));
+        assert!(!matches("r/48.1/11.6/10", &position()));
+        assert!(!matches("r/48.1/11.6/100", &message()));
+    }
+
+    #[test]
+    fn area() {
+        assert!(matches("a/49/12/48/13", &position()));
+        assert!(!matches("a/48/12/47/13", &position()));
+    }
+
+    #[test]
+    fn callsigns() {
+        assert!(matches("p/IC/DL", &position()));
+        assert!(!matches("p/DL", &position()));
+        assert!(matches("b/N0CALL-5", &message()));
+        assert!(matches("b/n0call*", &message()));
+        assert!(!matches("b/N0CALL", &message()));
+        assert!(matches("b/N0CALL-?", &message()));
+    }
+
+    #[test]
+    fn objects() {
+        let object = packet("N0CALL>APRS:;LEADER   *092345z4903.50N/07201.75W>");
+        assert!(matches("o/LEADER", &object));
+        assert!(matches("o/FOO/lead*", &object));
+        assert!(!matches("o/LEAD", &object));
+        assert!(!matches("o/LEADER", &message()));
+        assert!(!matches("o/N0CALL", &object));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_match(b"*", b""));
+        assert!(wildcard_match(b"a*b?d", b"AXXbcd"));
+        assert!(wildcard_match(b"*a*b", b"aab"));
+        assert!(!wildcard_match(b"a*b", b"ab c"));
+        assert!(!wildcard_match(b"?", b""));
+
+        // would take exponential time with naive backtracking
+        let s = [b'a'; 100];
+        assert!(!wildcard_match(b"*a*a*a*a*a*a*a*a*a*a*b", &s));
+    }
+
+    #[test]
+    fn types() {
+        assert!(matches("t/p", &position()));
+        assert!(!matches("t/w", &position()));
+        assert!(matches("t/m", &message()));
+        assert!(matches("t/q", &message()));
+        assert!(!matches("t/p", &message()));
+        assert!(matches("t/u", &packet("N0CALL>APRS:{Q1abc")));
+        assert!(!matches("t/u", &message()));
+        assert!(matches("t/s", &packet("N0CALL>APRS:>status")));
+        assert!(matches("t/o", &packet("N0CALL>APRS:;OBJECT   *111111z")));
+        assert!(!matches("t/s", &packet("N0CALL>APRS:")));
+    }
+
+    #[test]
+    fn symbol() {
+        assert!(matches(r"s//^", &position()));
+        assert!(!matches(r"s/^", &position()));
+        assert!(matches(r"s//^/\", &position()));
+        assert!(!matches(r"s//^/D", &position()));
+    }
+
+    #[test]
+    fn path() {
+        assert!(matches("d/WIDE1", &position()));
+        assert!(!matches("d/dl4mea", &position()));
+        assert!(matches("e/DL4MEA", &position()));
+        assert!(matches("e/T2*", &message()));
+        assert!(matches("q/R", &position()));
+        assert!(!matches("q/C", &position()));
+        assert!(matches("q/C", &message()));
+        assert!(matches("g/DEST", &message()));
+    }
+
+    #[test]
+    fn exclusions() {
+        assert!(matches("t/pm -b/N0CALL-5", &position()));
+        assert!(!matches("t/pm -b/N0CALL-5", &message()));
+        // exclusions alone never pass anything
+        assert!(!matches("-b/N0CALL-5", &position()));
+    }
+
+    #[test]
+    fn context() {
+        struct Context;
+
+        impl FilterContext for Context {
+            fn own_position(&self) -> Option<(Latitude, Longitude)> {
+                Some((Latitude::new(48.0).unwrap(), Longitude::new(12.0).unwrap()))
+            }
+
+            fn station_position(&self, callsign: &str) -> Option<(Latitude, Longitude)> {
+                match callsign {
+                    "FRIEND" => Some((Latitude::new(48.4).unwrap(), Longitude::new(12.4).unwrap())),
+                    _ => None,
+                }
+            }
+        }
+
+        let filter = Filter::from_str("m/100").unwrap();
+        assert!(filter.matches_with(&position(), &Context));
+        assert!(!filter.matches(&position()));
+
+        let filter = Filter::from_str("f/FRIEND/10").unwrap();
+        assert!(filter.matches_with(&position(), &Context));
+
+        let filter = Filter::from_str("t/p/FRIEND/1 f/OTHER/100").unwrap();
+        assert!(!filter.matches_with(&position(), &Context));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Filter::from_str("r/48/12"),
+            Err(FilterError::MissingArguments("r/48/12".to_owned()))
+        );
+        assert_eq!(
+            Filter::from_str("r/48/abc/50"),
+            Err(FilterError::InvalidArgument {
+                filter: "r/48/abc/50".to_owned(),
+                argument: "abc".to_owned()
+            })
+        );
+        assert_eq!(
+            Filter::from_str("r/98/12/50"),
+            Err(FilterError::InvalidArgument {
+                filter: "r/98/12/50".to_owned(),
+                argument: "98".to_owned()
+            })
+        );
+        assert_eq!(
+            Filter::from_str("t/px"),
+            Err(FilterError::InvalidPacketType('x'))
+        );
+        assert_eq!(
+            Filter::from_str("x/foo"),
+            Err(FilterError::UnknownFilter("x/foo".to_owned()))
+        );
+        assert_eq!(
+            Filter::from_str("b/"),
+            Err(FilterError::MissingArguments("b/".to_owned()))
+        );
+    }
+
+    #[test]
+    fn display() {
+        let s = "r/48.1/11.6/100 -t/wn b/N0CALL*/DL1ABC q/CX/I f/FRIEND/5 a/49/12/48/13";
+        assert_eq!(Filter::from_str(s).unwrap().to_string(), s);
+    }
+}
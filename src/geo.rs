@@ -0,0 +1,402 @@
+// distances and bearings between positions
+//
+// Distances, bearings, destinations and bounding boxes use a sphere with the
+// mean earth radius, which is accurate to about 0.5%. `vincenty_distance`
+// uses the WGS84 ellipsoid instead and is accurate to millimeters, at the
+// cost of an iteration that does not converge for nearly antipodal points.
+
+use core::f64::consts::PI;
+
+use math;
+use Angle;
+use AprsPosition;
+use AprsPositionRef;
+use Latitude;
+use Length;
+use Longitude;
+
+pub(crate) const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// WGS84
+const ELLIPSOID_A_KM: f64 = 6378.137;
+const ELLIPSOID_F: f64 = 1.0 / 298.257_223_563;
+
+/// A point on the earth's surface
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GeoPoint {
+    pub latitude: Latitude,
+    pub longitude: Longitude,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: Latitude, longitude: Longitude) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// `None` if the latitude or longitude is out of range
+    pub fn from_degrees(latitude: f64, longitude: f64) -> Option<Self> {
+        Some(Self::new(
+            Latitude::new(latitude)?,
+            Longitude::new(longitude)?,
+        ))
+    }
+
+    /// Great-circle distance (haversine)
+    pub fn distance(&self, other: &GeoPoint) -> Length {
+        let (lat1, lon1) = self.radians();
+        let (lat2, lon2) = other.radians();
+
+        let h = math::powi(math::sin((lat2 - lat1) / 2.0), 2)
+            + math::cos(lat1) * math::cos(lat2) * math::powi(math::sin((lon2 - lon1) / 2.0), 2);
+
+        // rounding errors can push `h` slightly above 1 for antipodal points
+        let h = if h > 1.0 { 1.0 } else { h };
+
+        Length::from_kilometers(2.0 * EARTH_RADIUS_KM * math::asin(math::sqrt(h)))
+    }
+
+    /// Distance on the WGS84 ellipsoid (Vincenty's inverse formula).
+    /// `None` if the iteration does not converge, which only happens for
+    /// nearly antipodal points.
+    pub fn vincenty_distance(&self, other: &GeoPoint) -> Option<Length> {
+        let (lat1, lon1) = self.radians();
+        let (lat2, lon2) = other.radians();
+
+        let f = ELLIPSOID_F;
+        let a = ELLIPSOID_A_KM;
+        let b = (1.0 - f) * a;
+
+        let l = lon2 - lon1;
+        let u1 = math::atan((1.0 - f) * math::tan(lat1));
+        let u2 = math::atan((1.0 - f) * math::tan(lat2));
+        let (sin_u1, cos_u1) = (math::sin(u1), math::cos(u1));
+        let (sin_u2, cos_u2) = (math::sin(u2), math::cos(u2));
+
+        let mut lambda = l;
+        for _ in 0..200 {
+            let (sin_lambda, cos_lambda) = (math::sin(lambda), math::cos(lambda));
+
+            let sin_sigma = math::sqrt(
+                math::powi(cos_u2 * sin_lambda, 2)
+                    + math::powi(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda, 2),
+            );
+            if sin_sigma == 0.0 {
+                // coincident points
+                return Some(Length::default());
+            }
+
+            let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+            let sigma = math::atan2(sin_sigma, cos_sigma);
+            let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+            let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+            // zero on the equator
+            let cos_2sigma_m = if cos_sq_alpha == 0.0 {
+                0.0
+            } else {
+                cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+            };
+
+            let c = f / 16.0 * cos_sq_alpha * (4.0 + f * (4.0 - 3.0 * cos_sq_alpha));
+            let previous = lambda;
+            lambda = l
+                + (1.0 - c)
+                    * f
+                    * sin_alpha
+                    * (sigma
+                        + c * sin_sigma
+                            * (cos_2sigma_m
+                                + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)));
+
+            if math::abs(lambda - previous) < 1e-12 {
+                let u_sq = cos_sq_alpha * (a * a - b * b) / (b * b);
+                let big_a = 1.0
+                    + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+                let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+                let delta_sigma = big_b
+                    * sin_sigma
+                    * (cos_2sigma_m
+                        + big_b / 4.0
+                            * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)
+                                - big_b / 6.0
+                                    * cos_2sigma_m
+                                    * (-3.0 + 4.0 * sin_sigma * sin_sigma)
+                                    * (-3.0 + 4.0 * cos_2sigma_m * cos_2sigma_m)));
+
+                return Some(Length::from_kilometers(b * big_a * (sigma - delta_sigma)));
+            }
+        }
+
+        None
+    }
+
+    /// Initial bearing towards `other` from north, 0 to 360 degrees
+    pub fn initial_bearing(&self, other: &GeoPoint) -> Angle {
+        let (lat1, lon1) = self.radians();
+        let (lat2, lon2) = other.radians();
+        let dlon = lon2 - lon1;
+
+        let y = math::sin(dlon) * math::cos(lat2);
+        let x =
+            math::cos(lat1) * math::sin(lat2) - math::sin(lat1) * math::cos(lat2) * math::cos(dlon);
+
+        normalize_bearing(to_degrees(math::atan2(y, x)))
+    }
+
+    /// Bearing when arriving at `other` from north, 0 to 360 degrees
+    pub fn final_bearing(&self, other: &GeoPoint) -> Angle {
+        normalize_bearing(other.initial_bearing(self).degrees() + 180.0)
+    }
+
+    /// The point reached by travelling `distance` along the great circle
+    /// that starts at `bearing` from north. `None` for non-finite arguments.
+    pub fn destination(&self, bearing: Angle, distance: Length) -> Option<Self> {
+        let (lat1, lon1) = self.radians();
+        let bearing = bearing.radians();
+        let angle = distance.kilometers() / EARTH_RADIUS_KM;
+
+        let lat2 = math::asin(
+            math::sin(lat1) * math::cos(angle)
+                + math::cos(lat1) * math::sin(angle) * math::cos(bearing),
+        );
+        let lon2 = lon1
+            + math::atan2(
+                math::sin(bearing) * math::sin(angle) * math::cos(lat1),
+                math::cos(angle) - math::sin(lat1) * math::sin(lat2),
+            );
+
+        Self::from_degrees(to_degrees(lat2), normalize_longitude(to_degrees(lon2)))
+    }
+
+    /// The smallest box that contains every point within `distance`
+    pub fn bounding_box(&self, distance: Length) -> BoundingBox {
+        let (lat, lon) = self.radians();
+        let angle = distance.kilometers() / EARTH_RADIUS_KM;
+
+        let mut south = lat - angle;
+        let mut north = lat + angle;
+        let (west, east);
+
+        if south > -PI / 2.0 && north < PI / 2.0 {
+            let dlon = math::asin(math::sin(angle) / math::cos(lat));
+            west = normalize_longitude(to_degrees(lon - dlon));
+            east = normalize_longitude(to_degrees(lon + dlon));
+        } else {
+            // a pole is within the distance, all longitudes are
+            south = south.max(-PI / 2.0);
+            north = north.min(PI / 2.0);
+            west = -180.0;
+            east = 180.0;
+        }
+
+        BoundingBox {
+            south: Latitude::new(to_degrees(south).max(-90.0)).unwrap_or_default(),
+            north: Latitude::new(to_degrees(north).min(90.0)).unwrap_or_default(),
+            west: Longitude::new(west).unwrap_or_default(),
+            east: Longitude::new(east).unwrap_or_default(),
+        }
+    }
+
+    fn radians(&self) -> (f64, f64) {
+        (to_radians(*self.latitude), to_radians(*self.longitude))
+    }
+}
+
+impl From<&AprsPosition> for GeoPoint {
+    fn from(position: &AprsPosition) -> Self {
+        Self::new(position.latitude, position.longitude)
+    }
+}
+
+impl<'a> From<&AprsPositionRef<'a>> for GeoPoint {
+    fn from(position: &AprsPositionRef<'a>) -> Self {
+        Self::new(position.latitude, position.longitude)
+    }
+}
+
+/// Area between two latitudes and two longitudes. `west` is greater than
+/// `east` if the box crosses the 180th meridian.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub south: Latitude,
+    pub west: Longitude,
+    pub north: Latitude,
+    pub east: Longitude,
+}
+
+impl BoundingBox {
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        let lat = *point.latitude;
+        let lon = *point.longitude;
+
+        let within_longitudes = if *self.west <= *self.east {
+            lon >= *self.west && lon <= *self.east
+        } else {
+            lon >= *self.west || lon <= *self.east
+        };
+
+        lat >= *self.south && lat <= *self.north && within_longitudes
+    }
+}
+
+fn to_radians(degrees: f64) -> f64 {
+    degrees * (PI / 180.0)
+}
+
+fn to_degrees(radians: f64) -> f64 {
+    radians * (180.0 / PI)
+}
+
+fn normalize_bearing(degrees: f64) -> Angle {
+    Angle::from_degrees((degrees % 360.0 + 360.0) % 360.0)
+}
+
+fn normalize_longitude(degrees: f64) -> f64 {
+    let lon = (degrees + 540.0) % 360.0 - 180.0;
+    // keep the east side of the 180th meridian
+    if lon == -180.0 && degrees > 0.0 {
+        180.0
+    } else {
+        lon
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn point(latitude: f64, longitude: f64) -> GeoPoint {
+        GeoPoint::from_degrees(latitude, longitude).unwrap()
+    }
+
+    #[test]
+    fn distance() {
+        let berlin = point(52.5200, 13.4050);
+        let munich = point(48.1351, 11.5820);
+
+        assert_relative_eq!(berlin.distance(&munich).kilometers(), 504.4, epsilon = 0.1);
+        assert_relative_eq!(
+            berlin.distance(&munich).statute_miles(),
+            313.4,
+            epsilon = 0.1
+        );
+        assert_relative_eq!(
+            berlin.distance(&munich).nautical_miles(),
+            272.3,
+            epsilon = 0.1
+        );
+        assert_eq!(berlin.distance(&berlin), Length::default());
+
+        // antipodal
+        assert_relative_eq!(
+            point(0.0, 0.0).distance(&point(0.0, 180.0)).kilometers(),
+            PI * EARTH_RADIUS_KM
+        );
+    }
+
+    #[test]
+    fn vincenty_distance() {
+        // Flinders Peak to Buninyong, Vincenty's own example
+        let flinders_peak = point(-37.951_033_416_7, 144.424_867_888_9);
+        let buninyong = point(-37.652_821_138_9, 143.926_495_527_8);
+
+        assert_relative_eq!(
+            flinders_peak
+                .vincenty_distance(&buninyong)
+                .unwrap()
+                .kilometers(),
+            54.972_271,
+            epsilon = 1e-6
+        );
+        assert_eq!(
+            flinders_peak.vincenty_distance(&flinders_peak),
+            Some(Length::default())
+        );
+        assert_eq!(point(0.0, 0.0).vincenty_distance(&point(0.5, 179.7)), None);
+    }
+
+    #[test]
+    fn bearings() {
+        // Land's End to John o' Groats
+        let a = point(50.066_389, -5.714_722);
+        let b = point(58.643_889, -3.07);
+
+        assert_relative_eq!(a.initial_bearing(&b).degrees(), 9.1198, epsilon = 1e-4);
+        assert_relative_eq!(a.final_bearing(&b).degrees(), 11.2752, epsilon = 1e-4);
+
+        assert_relative_eq!(
+            point(0.0, 0.0).initial_bearing(&point(0.0, 1.0)).degrees(),
+            90.0
+        );
+        assert_relative_eq!(
+            point(0.0, 0.0).initial_bearing(&point(0.0, -1.0)).degrees(),
+            270.0
+        );
+        assert_relative_eq!(
+            point(0.0, 0.0).initial_bearing(&point(-1.0, 0.0)).degrees(),
+            180.0
+        );
+    }
+
+    #[test]
+    fn destination() {
+        let start = point(53.3206, -1.7297);
+        let dest = start
+            .destination(Angle::from_degrees(96.0211), Length::from_kilometers(124.8))
+            .unwrap();
+        assert_relative_eq!(*dest.latitude, 53.1883, epsilon = 1e-4);
+        assert_relative_eq!(*dest.longitude, 0.1333, epsilon = 1e-4);
+
+        // across the 180th meridian
+        let dest = point(0.0, 179.5)
+            .destination(Angle::from_degrees(90.0), Length::from_nautical_miles(60.0))
+            .unwrap();
+        assert_relative_eq!(*dest.longitude, -179.5, epsilon = 1e-3);
+
+        assert_eq!(
+            start.destination(Angle::from_degrees(f64::NAN), Length::from_meters(1.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn bounding_box() {
+        let munich = point(48.1351, 11.5820);
+        let bbox = munich.bounding_box(Length::from_kilometers(100.0));
+        assert_relative_eq!(*bbox.north - *munich.latitude, 0.8993, epsilon = 1e-4);
+        assert_relative_eq!(*munich.latitude - *bbox.south, 0.8993, epsilon = 1e-4);
+        assert_relative_eq!(*bbox.east - *munich.longitude, 1.3476, epsilon = 1e-4);
+        assert!(bbox.contains(&munich));
+
+        for bearing in (0..360).step_by(15) {
+            let edge = munich
+                .destination(
+                    Angle::from_degrees(bearing as f64),
+                    Length::from_kilometers(99.9),
+                )
+                .unwrap();
+            assert!(bbox.contains(&edge), "bearing {}", bearing);
+        }
+        assert!(!bbox.contains(&point(48.1351, 13.0)));
+
+        let fiji = point(-17.7, 179.9).bounding_box(Length::from_statute_miles(50.0));
+        assert!(*fiji.west > *fiji.east);
+        assert!(fiji.contains(&point(-17.7, -179.9)));
+        assert!(!fiji.contains(&point(-17.7, 0.0)));
+
+        let pole = point(89.5, 0.0).bounding_box(Length::from_kilometers(100.0));
+        assert_eq!((*pole.north, *pole.west, *pole.east), (90.0, -180.0, 180.0));
+        assert!(pole.contains(&point(89.5, 180.0)));
+    }
+
+    #[test]
+    fn from_position() {
+        let position = AprsPosition::try_from(&br"!4903.50N/07201.75W-"[..]).unwrap();
+        let point = GeoPoint::from(&position);
+        assert_relative_eq!(*point.latitude, 49.058333, epsilon = 1e-6);
+        assert_relative_eq!(*point.longitude, -72.029166, epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,399 @@
+// KISS framing as spoken by software TNCs like Direwolf and most serial TNCs
+//
+// Frames are delimited by FEND bytes. FEND and FESC inside a frame are
+// escaped as FESC TFEND and FESC TFESC. The first byte of every frame holds
+// the TNC port in the high nibble and the command in the low nibble.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use AprsError;
+use AprsPacket;
+use EncodeError;
+use EncodeWrite;
+
+pub const FEND: u8 = 0xc0;
+pub const FESC: u8 = 0xdb;
+pub const TFEND: u8 = 0xdc;
+pub const TFESC: u8 = 0xdd;
+
+// longest unescaped frame the decoder buffers, well above the largest AX.25
+// frames TNCs send. Longer frames are dropped.
+const MAX_FRAME_LENGTH: usize = 2048;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum KissCommand {
+    Data,
+    TxDelay,
+    Persistence,
+    SlotTime,
+    TxTail,
+    FullDuplex,
+    SetHardware,
+    Return,
+    Unknown(u8),
+}
+
+impl From<u8> for KissCommand {
+    fn from(byte: u8) -> Self {
+        match byte {
+            0x00 => KissCommand::Data,
+            0x01 => KissCommand::TxDelay,
+            0x02 => KissCommand::Persistence,
+            0x03 => KissCommand::SlotTime,
+            0x04 => KissCommand::TxTail,
+            0x05 => KissCommand::FullDuplex,
+            0x06 => KissCommand::SetHardware,
+            0x0f => KissCommand::Return,
+            x => KissCommand::Unknown(x),
+        }
+    }
+}
+
+impl From<KissCommand> for u8 {
+    fn from(command: KissCommand) -> u8 {
+        match command {
+            KissCommand::Data => 0x00,
+            KissCommand::TxDelay => 0x01,
+            KissCommand::Persistence => 0x02,
+            KissCommand::SlotTime => 0x03,
+            KissCommand::TxTail => 0x04,
+            KissCommand::FullDuplex => 0x05,
+            KissCommand::SetHardware => 0x06,
+            KissCommand::Return => 0x0f,
+            KissCommand::Unknown(x) => x,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct KissFrame {
+    /// TNC port, `0..=15`
+    pub port: u8,
+    pub command: KissCommand,
+    pub data: Vec<u8>,
+}
+
+impl KissFrame {
+    /// Creates a data frame containing the packet as an AX.25 UI frame
+    pub fn from_packet(port: u8, packet: &AprsPacket) -> Result<Self, EncodeError> {
+        let mut data = vec![];
+        packet.encode_ax25(&mut data)?;
+
+        Ok(Self {
+            port,
+            command: KissCommand::Data,
+            data,
+        })
+    }
+
+    /// Parses the AX.25 payload of a data frame.
+    /// Returns `None` for all other commands.
+    pub fn to_packet(&self) -> Option<Result<AprsPacket, AprsError>> {
+        match self.command {
+            KissCommand::Data => Some(AprsPacket::parse_ax25(&self.data)),
+            _ => None,
+        }
+    }
+
+    pub fn encode<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
+        if self.port > 0x0f {
+            return Err(EncodeError::InvalidKissPort(self.port));
+        }
+
+        let command = u8::from(self.command);
+        if command > 0x0f {
+            return Err(EncodeError::InvalidKissCommand(command));
+        }
+
+        // the return command always occupies the whole byte
+        let header = match self.command {
+            KissCommand::Return if self.port != 0 => {
+                return Err(EncodeError::InvalidKissPort(self.port));
+            }
+            KissCommand::Return => 0xff,
+            _ => (self.port << 4) | command,
+        };
+
+        buf.write_all(&[FEND])?;
+        write_escaped(&[header], buf)?;
+        write_escaped(&self.data, buf)?;
+        buf.write_all(&[FEND])?;
+
+        Ok(())
+    }
+
+    fn parse(b: &[u8]) -> Option<Self> {
+        let (&header, data) = b.split_first()?;
+
+        let (port, command) = match header {
+            0xff => (0, KissCommand::Return),
+            // not a return, which has no port
+            _ if header & 0x0f == 0x0f => (header >> 4, KissCommand::Unknown(0x0f)),
+            _ => (header >> 4, KissCommand::from(header & 0x0f)),
+        };
+
+        Some(Self {
+            port,
+            command,
+            data: data.to_owned(),
+        })
+    }
+}
+
+fn write_escaped<W: EncodeWrite>(data: &[u8], buf: &mut W) -> Result<(), EncodeError> {
+    for chunk in data.split_inclusive(|b| *b == FEND || *b == FESC) {
+        let (last, head) = chunk.split_last().unwrap();
+        buf.write_all(head)?;
+        match *last {
+            FEND => buf.write_all(&[FESC, TFEND])?,
+            FESC => buf.write_all(&[FESC, TFESC])?,
+            x => buf.write_all(&[x])?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Incremental KISS decoder
+///
+/// Bytes can be fed in arbitrary chunks, e.g. straight from a serial port or
+/// TCP socket. Complete frames are queued until they are taken out with
+/// [`KissDecoder::next_frame`] or [`KissDecoder::next_packet`]. Frames longer
+/// than 2048 bytes are dropped.
+#[derive(Clone, Debug, Default)]
+pub struct KissDecoder {
+    buf: Vec<u8>,
+    in_frame: bool,
+    escaped: bool,
+    frames: VecDeque<KissFrame>,
+}
+
+impl KissDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            if b == FEND {
+                // back-to-back FENDs are used as padding and produce no frame
+                if let Some(frame) = KissFrame::parse(&self.buf) {
+                    self.frames.push_back(frame);
+                }
+                self.buf.clear();
+                self.in_frame = true;
+                self.escaped = false;
+                continue;
+            }
+
+            // bytes before the first FEND belong to a frame we missed the start of
+            if !self.in_frame {
+                continue;
+            }
+
+            if self.buf.len() == MAX_FRAME_LENGTH {
+                // skip the rest of the frame
+                self.buf.clear();
+                self.in_frame = false;
+                continue;
+            }
+
+            if self.escaped {
+                self.escaped = false;
+                self.buf.push(match b {
+                    TFEND => FEND,
+                    TFESC => FESC,
+                    // protocol violation, keep the byte as is
+                    x => x,
+                });
+            } else if b == FESC {
+                self.escaped = true;
+            } else {
+                self.buf.push(b);
+            }
+        }
+    }
+
+    pub fn next_frame(&mut self) -> Option<KissFrame> {
+        self.frames.pop_front()
+    }
+
+    /// Returns the next data frame, parsed as an AX.25 APRS packet.
+    /// Frames with other commands are skipped.
+    pub fn next_packet(&mut self) -> Option<Result<AprsPacket, AprsError>> {
+        while let Some(frame) = self.next_frame() {
+            if let Some(packet) = frame.to_packet() {
+                return Some(packet);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn packet() -> AprsPacket {
+        AprsPacket::try_from(&b"N0CALL-7>APRS,WIDE1-1,WIDE2-2:!4903.50N/07201.75W-Test"[..])
+            .unwrap()
+    }
+
+    #[test]
+    fn encode_escapes() {
+        let frame = KissFrame {
+            port: 2,
+            command: KissCommand::Data,
+            data: vec![0x01, FEND, 0x02, FESC, 0x03],
+        };
+
+        let mut buf = vec![];
+        frame.encode(&mut buf).unwrap();
+        assert_eq!(
+            buf,
+            vec![FEND, 0x20, 0x01, FESC, TFEND, 0x02, FESC, TFESC, 0x03, FEND]
+        );
+    }
+
+    #[test]
+    fn encode_invalid_port() {
+        let frame = KissFrame {
+            port: 16,
+            command: KissCommand::Data,
+            data: vec![],
+        };
+
+        let mut buf = vec![];
+        assert!(matches!(
+            frame.encode(&mut buf),
+            Err(EncodeError::InvalidKissPort(16))
+        ));
+    }
+
+    #[test]
+    fn decode_escapes() {
+        let mut decoder = KissDecoder::new();
+        decoder.feed(&[FEND, 0x20, 0x01, FESC, TFEND, 0x02, FESC, TFESC, 0x03, FEND]);
+
+        assert_eq!(
+            decoder.next_frame(),
+            Some(KissFrame {
+                port: 2,
+                command: KissCommand::Data,
+                data: vec![0x01, FEND, 0x02, FESC, 0x03],
+            })
+        );
+        assert_eq!(decoder.next_frame(), None);
+    }
+
+    #[test]
+    fn decode_in_chunks() {
+        let mut buf = vec![];
+        KissFrame::from_packet(0, &packet())
+            .unwrap()
+            .encode(&mut buf)
+            .unwrap();
+
+        // leading garbage and padding FENDs are ignored
+        let mut stream = vec![0x42, 0x43, FEND, FEND];
+        stream.extend_from_slice(&buf);
+        stream.extend_from_slice(&buf);
+
+        let mut decoder = KissDecoder::new();
+        for chunk in stream.chunks(3) {
+            decoder.feed(chunk);
+        }
+
+        assert_eq!(decoder.next_packet(), Some(Ok(packet())));
+        assert_eq!(decoder.next_packet(), Some(Ok(packet())));
+        assert_eq!(decoder.next_packet(), None);
+    }
+
+    #[test]
+    fn decode_skips_non_data_frames() {
+        let mut decoder = KissDecoder::new();
+        decoder.feed(&[FEND, 0x01, 50, FEND]);
+
+        let mut buf = vec![];
+        KissFrame::from_packet(1, &packet())
+            .unwrap()
+            .encode(&mut buf)
+            .unwrap();
+        decoder.feed(&buf);
+
+        assert_eq!(decoder.next_packet(), Some(Ok(packet())));
+        assert_eq!(decoder.next_packet(), None);
+    }
+
+    #[test]
+    fn return_command() {
+        let frame = KissFrame {
+            port: 0,
+            command: KissCommand::Return,
+            data: vec![],
+        };
+
+        let mut buf = vec![];
+        frame.encode(&mut buf).unwrap();
+        assert_eq!(buf, vec![FEND, 0xff, FEND]);
+
+        let mut decoder = KissDecoder::new();
+        decoder.feed(&buf);
+        assert_eq!(decoder.next_frame(), Some(frame));
+
+        let frame = KissFrame {
+            port: 3,
+            command: KissCommand::Return,
+            data: vec![],
+        };
+        assert!(matches!(
+            frame.encode(&mut buf),
+            Err(EncodeError::InvalidKissPort(3))
+        ));
+
+        // only 0xff is a return, other ports keep their command byte
+        decoder.feed(&[FEND, 0x3f, FEND]);
+        let frame = decoder.next_frame().unwrap();
+        assert_eq!(frame.port, 3);
+        assert_eq!(frame.command, KissCommand::Unknown(0x0f));
+
+        let mut buf = vec![];
+        frame.encode(&mut buf).unwrap();
+        assert_eq!(buf, vec![FEND, 0x3f, FEND]);
+    }
+
+    #[test]
+    fn decode_drops_oversized_frames() {
+        let mut decoder = KissDecoder::new();
+        decoder.feed(&[FEND, 0x00]);
+        for _ in 0..10 {
+            decoder.feed(&[0x42; MAX_FRAME_LENGTH]);
+        }
+        assert!(decoder.buf.len() <= MAX_FRAME_LENGTH);
+
+        decoder.feed(&[FEND, 0x00, 0x42, FEND]);
+        assert_eq!(
+            decoder.next_frame(),
+            Some(KissFrame {
+                port: 0,
+                command: KissCommand::Data,
+                data: vec![0x42],
+            })
+        );
+        assert_eq!(decoder.next_frame(), None);
+
+        // the longest frame still fits
+        let mut frame = vec![FEND];
+        frame.extend_from_slice(&[0x00; MAX_FRAME_LENGTH]);
+        frame.push(FEND);
+        decoder.feed(&frame);
+        assert_eq!(
+            decoder.next_frame().unwrap().data.len(),
+            MAX_FRAME_LENGTH - 1
+        );
+    }
+}
@@ -8,96 +8,176 @@
 //! ```rust
 //! extern crate aprs_parser;
 //!
-//! use aprs_parser::{AprsCst, AprsData, AprsPacket, AprsPosition, Callsign, Latitude, Longitude, Timestamp};
+//! use aprs_parser::{AprsCst, AprsData, Callsign, Precision, Timestamp};
 //!
 //! fn main() {
-//!     let result = aprs_parser::parse(
+//!     let packet = aprs_parser::parse(
 //!         br"ICA3D17F2>APRS,qAS,dl4mea:/074849h4821.61N\01224.49E^322/103/A=003054"
+//!     ).unwrap();
+//!
+//!     assert_eq!(packet.from, Callsign::new("ICA3D17F2", None));
+//!     assert_eq!(packet.to, Callsign::new("APRS", None));
+//!     assert_eq!(
+//!         packet.via,
+//!         vec![Callsign::new("qAS", None), Callsign::new("dl4mea", None)]
 //!     );
 //!
+//!     let position = match packet.data {
+//!         AprsData::Position(position) => position,
+//!         _ => panic!("not a position"),
+//!     };
+//!     assert_eq!(position.timestamp, Some(Timestamp::HHMMSS(7, 48, 49)));
+//!     assert!(!position.messaging_supported);
+//!     // coordinates compare their value and precision, dereference them for
+//!     // the value alone
+//!     assert_eq!(*position.latitude, 48.36016666666667);
+//!     assert_eq!(*position.longitude, 12.408166666666666);
 //!     assert_eq!(
-//!         result,
-//!         Ok(
-//!             AprsPacket {
-//!                 from: Callsign {
-//!                     call: "ICA3D17F2".to_string(),
-//!                     ssid: None
-//!                 },
-//!                 to: Callsign {
-//!                     call: "APRS".to_string(),
-//!                     ssid: None
-//!                 },
-//!                 via: vec![
-//!                     Callsign {
-//!                         call: "qAS".to_string(),
-//!                         ssid: None
-//!                     },
-//!                     Callsign {
-//!                         call: "dl4mea".to_string(),
-//!                         ssid: None
-//!                     }
-//!                 ],
-//!                 data: AprsData::Position(
-//!                     AprsPosition {
-//!                         timestamp: Some(
-//!                             Timestamp::HHMMSS(
-//!                                 7,
-//!                                 48,
-//!                                 49
-//!                             )
-//!                         ),
-//!                         messaging_supported: false,
-//!                         latitude: Latitude::new(48.36016666666667).unwrap(),
-//!                         longitude: Longitude::new(12.408166666666666).unwrap(),
-//!                         symbol_table: '\\',
-//!                         symbol_code: '^',
-//!                         comment: b"322/103/A=003054".to_vec(),
-//!                         cst: AprsCst::Uncompressed,
-//!                     }
-//!                 )
-//!             }
-//!        )
-//!    );
+//!         position.latitude.precision(),
+//!         Precision::Uncompressed { ambiguity: 0 }
+//!     );
+//!     assert_eq!(position.symbol_table, '\\');
+//!     assert_eq!(position.symbol_code, '^');
+//!     assert_eq!(position.comment, b"322/103/A=003054");
+//!     assert_eq!(position.cst, AprsCst::Uncompressed);
 //! }
 //! ```
+//!
+//! # Features
+//!
+//! - `std` (default): implements `std::error::Error`, encodes into any
+//!   `std::io::Write` and enables the APRS-IS client and line reader.
+//! - `libm`: floating point math for `no_std` builds. Without `std` the crate
+//!   only needs `alloc`, encoders write to a `Vec<u8>` or through
+//!   [`FmtWriter`] to any `core::fmt::Write`.
+//! - `serde`: `Serialize` and `Deserialize` for all packet types. Callsigns
+//!   are strings like `"N0CALL-7"`, coordinates are numbers and enums use
+//!   serde's default externally tagged representation, e.g.
+//!   `{"Position": {...}}` or `{"HHMMSS": [7, 48, 49]}`. Byte fields like
+//!   comments and message texts are lossy UTF-8 strings in human readable
+//!   formats and raw bytes in binary formats.
+//! - `serde-base64`: byte fields are standard base64 strings in human readable
+//!   formats instead, so non-UTF8 data survives a round trip through JSON.
+//! - `chrono`: [`Timestamp::to_datetime`] resolves timestamps to absolute UTC
+//!   date-times.
 
 // `!(-90. ..=90.).contains(&value)` seems worse than `value > 90. || value < -90.`
 #![allow(clippy::manual_range_contains)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(not(feature = "std"), not(feature = "libm")))]
+compile_error!("aprs-parser requires either the `std` or the `libm` feature");
 
-extern crate thiserror;
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(not(feature = "std"))]
+extern crate libm;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 
 #[cfg(test)]
 #[macro_use]
 extern crate approx;
 
+#[cfg(feature = "std")]
+mod aprsis;
+mod ax25;
 mod base91;
+mod builder;
 mod bytes;
 mod callsign;
+mod clock;
 mod compressed_cs;
 mod compression_type;
+mod device;
+mod dupe;
+mod encode;
 mod error;
+mod filter;
+mod geo;
+mod kiss;
 mod lonlat;
+mod math;
 mod message;
 mod packet;
 mod position;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod session;
+mod symbol;
 mod timestamp;
+mod units;
+mod user_defined;
 
-use std::convert::TryFrom;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
 
-pub use callsign::Callsign;
+#[cfg(feature = "std")]
+pub use aprsis::{passcode, AprsIsConnection, Login, LoginResponse, ServerBanner, ServerComment};
+pub use builder::{PacketBuilder, PositionBuilder};
+pub use callsign::{Callsign, CallsignRef};
+#[cfg(feature = "std")]
+pub use clock::SystemClock;
+pub use clock::{Clock, ManualClock};
 pub use compressed_cs::{AprsAltitude, AprsCompressedCs, AprsCourseSpeed, AprsRadioRange};
 pub use compression_type::AprsCompressionType;
-pub use error::{AprsError, EncodeError};
-pub use lonlat::{Latitude, Longitude};
-pub use message::AprsMessage;
-pub use packet::{AprsData, AprsPacket};
-pub use position::{AprsCst, AprsPosition};
-pub use timestamp::Timestamp;
+pub use device::{Device, DeviceClass, DeviceTable};
+pub use dupe::{DupeChecker, DupeStats};
+pub use encode::{EncodeWrite, FmtWriter};
+#[cfg(feature = "std")]
+pub use error::LoginError;
+pub use error::{
+    AprsError, DeviceTableError, EncodeError, FilterError, ParseError, ParseField, ParseWarning,
+};
+pub use filter::{Filter, FilterContext, FilterKind, FilterPart, PacketType};
+pub use geo::{BoundingBox, GeoPoint};
+pub use kiss::{KissCommand, KissDecoder, KissFrame, FEND, FESC, TFEND, TFESC};
+pub use lonlat::{Latitude, Longitude, Precision};
+pub use message::{AprsMessage, AprsMessageRef, MessageReassembler, MAX_MESSAGE_TEXT_LENGTH};
+pub use packet::{AprsData, AprsDataRef, AprsPacket, AprsPacketRef};
+pub use position::{AprsCst, AprsPosition, AprsPositionRef};
+#[cfg(feature = "std")]
+pub use reader::{AprsLine, AprsReader, ReadLine, DEFAULT_MAX_LINE_LENGTH};
+pub use session::{MessageEvent, MessageSession};
+pub use symbol::Symbol;
+pub use timestamp::{Timestamp, TimestampField};
+pub use units::{Angle, Length, Speed, TurnRate, VerticalSpeed};
+pub use user_defined::{AprsUserDefined, AprsUserDefinedRef, UserDefinedDecoders};
 
 pub fn parse(b: &[u8]) -> Result<AprsPacket, AprsError> {
     AprsPacket::try_from(b)
 }
 
+/// Parses a packet with an error that points to the bad bytes, see
+/// [`ParseError`]
+pub fn parse_detailed(b: &[u8]) -> Result<AprsPacket, ParseError> {
+    AprsPacket::parse_detailed(b)
+}
+
+/// Parses a packet and checks the ranges of timestamp fields, see
+/// [`AprsPacketRef::parse_strict`]
+pub fn parse_strict(b: &[u8]) -> Result<AprsPacket, ParseError> {
+    AprsPacket::parse_strict(b)
+}
+
+/// Parses a packet, recovering from malformed fields where possible, see
+/// [`AprsPacketRef::parse_lenient`]
+pub fn parse_lenient(b: &[u8]) -> Result<(AprsPacket, Vec<ParseWarning>), AprsError> {
+    AprsPacket::parse_lenient(b)
+}
+
+/// Parses a packet without copying any of its fields, see [`AprsPacketRef`]
+pub fn parse_ref(b: &[u8]) -> Result<AprsPacketRef<'_>, AprsError> {
+    AprsPacketRef::try_from(b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
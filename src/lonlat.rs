@@ -1,19 +1,71 @@
-use std::io::Write;
-use std::ops::Deref;
+use alloc::borrow::ToOwned;
+use core::cmp::Ordering;
+use core::ops::Deref;
 
 use base91;
-use bytes::parse_bytes;
+use math;
+use Angle;
 use AprsError;
 use EncodeError;
+use EncodeWrite;
+
+/// Source format and resolution of a coordinate
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Precision {
+    /// Created from a number, e.g. with [`Latitude::new`]
+    Exact,
+    /// `ddmm.hhN`, to 1/100 minute. `ambiguity` is the number of trailing
+    /// digits (0-4) sent as spaces, each one coarsens the resolution.
+    /// Ambiguous coordinates are placed in the middle of their area.
+    Uncompressed { ambiguity: u8 },
+    /// Base91, to about 0.3 m for latitudes and 0.6 m for longitudes
+    Compressed,
+    /// `ddmm.hhN` refined by a `!DAO!` extension in the comment, to 1/1000
+    /// minute with digits (`!W..!`) or 1/9100 minute with base91 (`!w..!`)
+    Dao { base91: bool },
+}
 
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Default)]
-pub struct Latitude(f64);
+// hundredths of a minute covered by each level of ambiguity
+const AMBIGUITY_HUNDREDTHS: [u32; 5] = [1, 10, 100, 1000, 6000];
+
+/// A latitude in degrees, with the precision it was parsed with.
+///
+/// Latitudes are only equal if they also encode the same, i.e. have the same
+/// precision and original minutes. Dereference them to compare the values
+/// alone.
+///
+/// serde only (de)serializes the value, deserialized latitudes are
+/// [`Precision::Exact`] and lose the ambiguity and original minutes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Latitude {
+    value: f64,
+    precision: Precision,
+    minutes: Option<DecimalMinutes>,
+}
 
 impl Deref for Latitude {
     type Target = f64;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.value
+    }
+}
+
+/// Orders by value, latitudes with the same value that aren't equal are
+/// unordered
+impl PartialOrd for Latitude {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        partial_cmp(self.value, other.value, self == other)
+    }
+}
+
+impl Default for Latitude {
+    fn default() -> Self {
+        Self {
+            value: 0.0,
+            precision: Precision::Exact,
+            minutes: None,
+        }
     }
 }
 
@@ -24,32 +76,46 @@ impl Latitude {
         if value > 90.0 || value < -90.0 || value.is_nan() {
             None
         } else {
-            Some(Self(value))
+            Some(Self {
+                value,
+                precision: Precision::Exact,
+                minutes: None,
+            })
         }
     }
 
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    /// Size of the area the value stands for, `None` for exact values
+    pub fn resolution(&self) -> Option<Angle> {
+        resolution(self.precision, 1.0 / 380926.0)
+    }
+
     pub(crate) fn parse_uncompressed(b: &[u8]) -> Result<Self, AprsError> {
-        if b.len() != 8 || b[4] != b'.' {
-            return Err(AprsError::InvalidLatitude(b.to_owned()));
+        let invalid = || AprsError::InvalidLatitude(b.to_owned());
+
+        if b.len() != 8 {
+            return Err(invalid());
         }
 
-        let north = match b[7] {
-            b'N' => true,
-            b'S' => false,
-            _ => return Err(AprsError::InvalidLatitude(b.to_owned())),
+        let minutes = DecimalMinutes::parse(b).ok_or_else(invalid)?;
+        let value = minutes.degrees(minutes.spaces);
+        let value = match minutes.hemisphere {
+            b'N' => value,
+            b'S' => -value,
+            _ => return Err(invalid()),
         };
 
-        let deg = parse_bytes::<u32>(&b[0..2])
-            .ok_or_else(|| AprsError::InvalidLatitude(b.to_owned()))? as f64;
-        let min = parse_bytes::<u32>(&b[2..4])
-            .ok_or_else(|| AprsError::InvalidLatitude(b.to_owned()))? as f64;
-        let min_frac = parse_bytes::<u32>(&b[5..7])
-            .ok_or_else(|| AprsError::InvalidLatitude(b.to_owned()))? as f64;
-
-        let value = deg + min / 60. + min_frac / 6_000.;
-        let value = if north { value } else { -value };
-
-        Self::new(value).ok_or_else(|| AprsError::InvalidLatitude(b.to_owned()))
+        let lat = Self::new(value).ok_or_else(invalid)?;
+        Ok(Self {
+            precision: Precision::Uncompressed {
+                ambiguity: minutes.spaces,
+            },
+            minutes: Some(minutes),
+            ..lat
+        })
     }
 
     pub(crate) fn parse_compressed(b: &[u8]) -> Result<Self, AprsError> {
@@ -60,40 +126,98 @@ impl Latitude {
             return Err(AprsError::InvalidLatitude(b.to_owned()));
         }
 
-        Ok(Self(value))
+        Ok(Self {
+            value,
+            precision: Precision::Compressed,
+            minutes: None,
+        })
     }
 
-    pub(crate) fn encode_compressed<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
-        let value = (90.0 - self.0) * 380926.0;
-        base91::encode_ascii(value, buf, 4)
+    pub(crate) fn encode_compressed<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
+        if !(-90.0..=90.0).contains(&self.value) {
+            return Err(EncodeError::InvalidLatitude(self.value));
+        }
+        check_compressed(self.precision)?;
+
+        let value = math::round((90.0 - self.value) * 380926.0);
+        base91::encode_ascii(value as u32, buf, 4)
     }
 
-    pub(crate) fn encode_uncompressed<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
-        let lat = self.0;
+    /// Keeps the ambiguity of uncompressed latitudes
+    pub(crate) fn encode_uncompressed<W: EncodeWrite>(
+        &self,
+        buf: &mut W,
+    ) -> Result<(), EncodeError> {
+        let lat = self.value;
 
         if !(-90.0..=90.0).contains(&lat) {
             return Err(EncodeError::InvalidLatitude(lat));
         }
+        check_uncompressed(self.precision)?;
+
+        let minutes = self.minutes.unwrap_or_else(|| {
+            let (dir, lat) = if lat.is_sign_positive() {
+                (b'N', lat)
+            } else {
+                (b'S', -lat)
+            };
+            DecimalMinutes::from_degrees(lat, self.ambiguity(), dir)
+        });
+        minutes.encode(buf, 2)
+    }
 
-        let (dir, lat) = if lat >= 0.0 { ('N', lat) } else { ('S', -lat) };
-
-        let deg = lat as u32;
-        let min = ((lat - (deg as f64)) * 60.0) as u32;
-        let min_frac = ((lat - (deg as f64) - (min as f64 / 60.0)) * 6000.0).round() as u32;
+    pub(crate) fn ambiguity(&self) -> u8 {
+        match self.precision {
+            Precision::Uncompressed { ambiguity } => ambiguity,
+            _ => 0,
+        }
+    }
 
-        write!(buf, "{:02}{:02}.{:02}{}", deg, min, min_frac, dir)?;
-        Ok(())
+    /// Adds the extra minutes of a DAO extension, see [`Dao::refine`]
+    pub(crate) fn refine(self, dao: &Dao) -> Self {
+        match dao.refine(self.value, self.precision, dao.latitude) {
+            Some(value) if (-90.0..=90.0).contains(&value) => Self {
+                value,
+                precision: dao.precision(),
+                ..self
+            },
+            _ => self,
+        }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Default)]
-pub struct Longitude(f64);
+/// A longitude in degrees, with the precision it was parsed with.
+///
+/// Compared like [`Latitude`], serde only (de)serializes the value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Longitude {
+    value: f64,
+    precision: Precision,
+    minutes: Option<DecimalMinutes>,
+}
 
 impl Deref for Longitude {
     type Target = f64;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.value
+    }
+}
+
+/// Orders by value, see [`Latitude`]
+impl PartialOrd for Longitude {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        partial_cmp(self.value, other.value, self == other)
+    }
+}
+
+impl Default for Longitude {
+    fn default() -> Self {
+        Self {
+            value: 0.0,
+            precision: Precision::Exact,
+            minutes: None,
+        }
     }
 }
 
@@ -104,33 +228,47 @@ impl Longitude {
         if value > 180.0 || value < -180.0 || value.is_nan() {
             None
         } else {
-            Some(Self(value))
+            Some(Self {
+                value,
+                precision: Precision::Exact,
+                minutes: None,
+            })
         }
     }
 
-    pub(crate) fn parse_uncompressed(b: &[u8]) -> Result<Self, AprsError> {
-        if b.len() != 9 || b[5] != b'.' {
-            return Err(AprsError::InvalidLongitude(b.to_owned()));
-        }
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
 
-        let east = match b[8] {
-            b'E' => true,
-            b'W' => false,
-            _ => return Err(AprsError::InvalidLongitude(b.to_owned())),
-        };
+    /// Size of the area the value stands for, `None` for exact values
+    pub fn resolution(&self) -> Option<Angle> {
+        resolution(self.precision, 1.0 / 190463.0)
+    }
 
-        let deg = parse_bytes::<u32>(&b[0..3])
-            .ok_or_else(|| AprsError::InvalidLongitude(b.to_owned()))? as f64;
-        let min = parse_bytes::<u32>(&b[3..5])
-            .ok_or_else(|| AprsError::InvalidLongitude(b.to_owned()))? as f64;
-        let min_frac = parse_bytes::<u32>(&b[6..8])
-            .ok_or_else(|| AprsError::InvalidLongitude(b.to_owned()))?
-            as f64;
+    /// The ambiguity of a position is set by its latitude, the longitude
+    /// may have digits in place of the spaces
+    pub(crate) fn parse_uncompressed(b: &[u8], ambiguity: u8) -> Result<Self, AprsError> {
+        let invalid = || AprsError::InvalidLongitude(b.to_owned());
+
+        if b.len() != 9 {
+            return Err(invalid());
+        }
 
-        let value = deg + min / 60. + min_frac / 6_000.;
-        let value = if east { value } else { -value };
+        let minutes = DecimalMinutes::parse(b).ok_or_else(invalid)?;
+        let ambiguity = ambiguity.max(minutes.spaces);
+        let value = minutes.degrees(ambiguity);
+        let value = match minutes.hemisphere {
+            b'E' => value,
+            b'W' => -value,
+            _ => return Err(invalid()),
+        };
 
-        Self::new(value).ok_or_else(|| AprsError::InvalidLongitude(b.to_owned()))
+        let lon = Self::new(value).ok_or_else(invalid)?;
+        Ok(Self {
+            precision: Precision::Uncompressed { ambiguity },
+            minutes: Some(minutes),
+            ..lon
+        })
     }
 
     pub(crate) fn parse_compressed(b: &[u8]) -> Result<Self, AprsError> {
@@ -142,29 +280,271 @@ impl Longitude {
             return Err(AprsError::InvalidLongitude(b.to_owned()));
         }
 
-        Ok(Self(value))
+        Ok(Self {
+            value,
+            precision: Precision::Compressed,
+            minutes: None,
+        })
     }
 
-    pub(crate) fn encode_compressed<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
-        let value = (180.0 + self.0) * 190463.0;
-        base91::encode_ascii(value, buf, 4)
+    pub(crate) fn encode_compressed<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
+        if !(-180.0..=180.0).contains(&self.value) {
+            return Err(EncodeError::InvalidLongitude(self.value));
+        }
+        check_compressed(self.precision)?;
+
+        let value = math::round((180.0 + self.value) * 190463.0);
+        base91::encode_ascii(value as u32, buf, 4)
     }
 
-    pub(crate) fn encode_uncompressed<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
-        let lon = self.0;
+    /// Keeps the ambiguity of uncompressed longitudes
+    pub(crate) fn encode_uncompressed<W: EncodeWrite>(
+        &self,
+        buf: &mut W,
+    ) -> Result<(), EncodeError> {
+        let lon = self.value;
 
         if !(-180.0..=180.0).contains(&lon) {
             return Err(EncodeError::InvalidLongitude(lon));
         }
+        check_uncompressed(self.precision)?;
+
+        let minutes = self.minutes.unwrap_or_else(|| {
+            let (dir, lon) = if lon.is_sign_positive() {
+                (b'E', lon)
+            } else {
+                (b'W', -lon)
+            };
+            DecimalMinutes::from_degrees(lon, self.ambiguity(), dir)
+        });
+        minutes.encode(buf, 3)
+    }
+
+    fn ambiguity(&self) -> u8 {
+        match self.precision {
+            Precision::Uncompressed { ambiguity } => ambiguity,
+            _ => 0,
+        }
+    }
+
+    /// Adds the extra minutes of a DAO extension, see [`Dao::refine`]
+    pub(crate) fn refine(self, dao: &Dao) -> Self {
+        match dao.refine(self.value, self.precision, dao.longitude) {
+            Some(value) if (-180.0..=180.0).contains(&value) => Self {
+                value,
+                precision: dao.precision(),
+                ..self
+            },
+            _ => self,
+        }
+    }
+}
+
+fn resolution(precision: Precision, compressed: f64) -> Option<Angle> {
+    match precision {
+        Precision::Exact => None,
+        Precision::Uncompressed { ambiguity } => Some(Angle::from_degrees(
+            AMBIGUITY_HUNDREDTHS[ambiguity as usize] as f64 / 6000.0,
+        )),
+        Precision::Compressed => Some(Angle::from_degrees(compressed)),
+        Precision::Dao { base91: false } => Some(Angle::from_degrees(0.001 / 60.0)),
+        Precision::Dao { base91: true } => Some(Angle::from_degrees(0.01 / 91.0 / 60.0)),
+    }
+}
+
+// values first, unequal coordinates with the same value are unordered
+fn partial_cmp(a: f64, b: f64, equal: bool) -> Option<Ordering> {
+    match a.partial_cmp(&b) {
+        Some(Ordering::Equal) if !equal => None,
+        ordering => ordering,
+    }
+}
+
+// compressed coordinates can't show ambiguity, and a DAO extension left in
+// the comment would be added to them again
+fn check_compressed(precision: Precision) -> Result<(), EncodeError> {
+    match precision {
+        Precision::Uncompressed { ambiguity: 1..=4 } | Precision::Dao { .. } => {
+            Err(EncodeError::UnsupportedPrecision(precision))
+        }
+        _ => Ok(()),
+    }
+}
+
+// rounding compressed coordinates to 1/100 minute would lose most of their
+// precision
+fn check_uncompressed(precision: Precision) -> Result<(), EncodeError> {
+    match precision {
+        Precision::Compressed => Err(EncodeError::UnsupportedPrecision(precision)),
+        _ => Ok(()),
+    }
+}
+
+/// A `!DAO!` extension in the comment of an uncompressed position, with
+/// the WGS84 datum `W`. The extra minutes are added to the magnitude of the
+/// coordinates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Dao {
+    base91: bool,
+    latitude: f64,
+    longitude: f64,
+}
+
+impl Dao {
+    /// The last DAO extension in `comment`
+    pub(crate) fn find(comment: &[u8]) -> Option<Self> {
+        comment.windows(5).rev().find_map(Self::parse)
+    }
+
+    // `!Wao!` with digits for the third decimal of the minutes, `!wao!` with
+    // base91 digits for about the third and fourth
+    fn parse(b: &[u8]) -> Option<Self> {
+        let base91 = |b: u8| (b'!'..=b'{').contains(&b);
+        let (base91, a, o) = match *b {
+            [b'!', b'W', a, o, b'!'] if a.is_ascii_digit() && o.is_ascii_digit() => {
+                (false, (a - b'0') as f64 / 1000.0, (o - b'0') as f64 / 1000.0)
+            }
+            [b'!', b'w', a, o, b'!'] if base91(a) && base91(o) => (
+                true,
+                (a - b'!') as f64 / 91.0 / 100.0,
+                (o - b'!') as f64 / 91.0 / 100.0,
+            ),
+            _ => return None,
+        };
+
+        Some(Self {
+            base91,
+            latitude: a,
+            longitude: o,
+        })
+    }
+
+    pub(crate) fn precision(&self) -> Precision {
+        Precision::Dao {
+            base91: self.base91,
+        }
+    }
+
+    /// Only coordinates parsed without ambiguity are refined
+    fn refine(&self, value: f64, precision: Precision, minutes: f64) -> Option<f64> {
+        if precision != (Precision::Uncompressed { ambiguity: 0 }) {
+            return None;
+        }
+
+        let degrees = minutes / 60.0;
+        Some(if value.is_sign_negative() {
+            value - degrees
+        } else {
+            value + degrees
+        })
+    }
+}
+
+/// An uncompressed coordinate as it was sent, so it can be encoded again
+/// byte for byte. Floats can't always do that, e.g. for `-0.0`, minutes
+/// above 59 or digits hidden by the ambiguity of the other coordinate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct DecimalMinutes {
+    /// `ddmmhh` or `dddmmhh`, spaces as 0
+    digits: u32,
+    spaces: u8,
+    hemisphere: u8,
+}
+
+impl DecimalMinutes {
+    // `ddmm.hhN` or `dddmm.hhE`. Up to four trailing digits may be spaces.
+    fn parse(b: &[u8]) -> Option<Self> {
+        let (hemisphere, b) = b.split_last()?;
+        let dot = b.len().checked_sub(3)?;
+        if b[dot] != b'.' {
+            return None;
+        }
+
+        let digits = || b[..dot].iter().chain(&b[dot + 1..]);
+        let spaces = digits().rev().take_while(|c| **c == b' ').count();
+        if spaces > 4 {
+            return None;
+        }
+
+        let mut value = 0;
+        for (i, d) in digits().enumerate() {
+            if i + spaces >= b.len() - 1 {
+                value *= 10;
+            } else if d.is_ascii_digit() {
+                value = value * 10 + (d - b'0') as u32;
+            } else {
+                return None;
+            }
+        }
+
+        Some(Self {
+            digits: value,
+            spaces: spaces as u8,
+            hemisphere: *hemisphere,
+        })
+    }
+
+    fn from_degrees(value: f64, ambiguity: u8, hemisphere: u8) -> Self {
+        let box_size = AMBIGUITY_HUNDREDTHS[ambiguity as usize];
+
+        // back from the middle of the area to its lower edge
+        let mut hundredths = math::round(value * 6_000.0) as u32;
+        if ambiguity > 0 {
+            hundredths = hundredths.saturating_sub(box_size / 2) / box_size * box_size;
+        }
+
+        Self {
+            digits: hundredths / 6_000 * 10_000 + hundredths % 6_000,
+            spaces: ambiguity,
+            hemisphere,
+        }
+    }
+
+    /// Magnitude in degrees, ignoring the `ambiguity` last digits and
+    /// moving to the middle of the area they cover
+    fn degrees(&self, ambiguity: u8) -> f64 {
+        let box_size = AMBIGUITY_HUNDREDTHS[ambiguity as usize];
+        let digits = match ambiguity {
+            0 => self.digits,
+            4 => self.digits / 10_000 * 10_000,
+            _ => self.digits / box_size * box_size,
+        };
+        let center = if ambiguity > 0 {
+            box_size as f64 / 2.0
+        } else {
+            0.0
+        };
 
-        let (dir, lon) = if lon >= 0.0 { ('E', lon) } else { ('W', -lon) };
+        let deg = (digits / 10_000) as f64;
+        let min = (digits / 100 % 100) as f64;
+        let min_frac = (digits % 100) as f64 + center;
+
+        deg + min / 60. + min_frac / 6_000.
+    }
 
-        let deg = lon as u32;
-        let min = ((lon - (deg as f64)) * 60.0) as u32;
-        let min_frac = ((lon - (deg as f64) - (min as f64 / 60.0)) * 6000.0).round() as u32;
+    fn encode<W: EncodeWrite>(&self, buf: &mut W, deg_digits: usize) -> Result<(), EncodeError> {
+        let mut out = [0; 9];
+        let len = deg_digits + 6;
+        let mut rest = self.digits;
+        for (i, b) in out[..len - 1].iter_mut().enumerate().rev() {
+            if i == deg_digits + 2 {
+                *b = b'.';
+            } else {
+                *b = b'0' + (rest % 10) as u8;
+                rest /= 10;
+            }
+        }
+        out[len - 1] = self.hemisphere;
+
+        // the minute digits, right to left, skipping the dot
+        for i in [len - 2, len - 3, len - 5, len - 6]
+            .iter()
+            .take(self.spaces as usize)
+        {
+            out[*i] = b' ';
+        }
 
-        write!(buf, "{:03}{:02}.{:02}{}", deg, min, min_frac, dir)?;
-        Ok(())
+        buf.write_all(&out[..len])
     }
 }
 
@@ -172,6 +552,22 @@ impl Longitude {
 mod tests {
     use super::*;
 
+    fn invalid_latitude(value: f64) -> Latitude {
+        Latitude {
+            value,
+            precision: Precision::Exact,
+            minutes: None,
+        }
+    }
+
+    fn invalid_longitude(value: f64) -> Longitude {
+        Longitude {
+            value,
+            precision: Precision::Exact,
+            minutes: None,
+        }
+    }
+
     #[test]
     fn test_parse_uncompressed_latitude() {
         assert_relative_eq!(
@@ -207,31 +603,31 @@ mod tests {
     #[test]
     fn test_parse_uncompressed_longitude() {
         assert_relative_eq!(
-            *Longitude::parse_uncompressed(&b"12903.50E"[..]).unwrap(),
+            *Longitude::parse_uncompressed(&b"12903.50E"[..], 0).unwrap(),
             129.05833333333333
         );
         assert_relative_eq!(
-            *Longitude::parse_uncompressed(&b"04903.50W"[..]).unwrap(),
+            *Longitude::parse_uncompressed(&b"04903.50W"[..], 0).unwrap(),
             -49.05833333333333
         );
         assert_eq!(
-            Longitude::parse_uncompressed(&b"04903.50N"[..]),
+            Longitude::parse_uncompressed(&b"04903.50N"[..], 0),
             Err(AprsError::InvalidLongitude(b"04903.50N".to_vec()))
         );
         assert_eq!(
-            Longitude::parse_uncompressed(&b"04903.50S"[..]),
+            Longitude::parse_uncompressed(&b"04903.50S"[..], 0),
             Err(AprsError::InvalidLongitude(b"04903.50S".to_vec()))
         );
         assert_eq!(
-            Longitude::parse_uncompressed(&b"18903.50E"[..]),
+            Longitude::parse_uncompressed(&b"18903.50E"[..], 0),
             Err(AprsError::InvalidLongitude(b"18903.50E".to_vec()))
         );
         assert_relative_eq!(
-            *Longitude::parse_uncompressed(&b"00000.00E"[..]).unwrap(),
+            *Longitude::parse_uncompressed(&b"00000.00E"[..], 0).unwrap(),
             0.0
         );
         assert_relative_eq!(
-            *Longitude::parse_uncompressed(&b"00000.00W"[..]).unwrap(),
+            *Longitude::parse_uncompressed(&b"00000.00W"[..], 0).unwrap(),
             0.0
         );
     }
@@ -239,50 +635,279 @@ mod tests {
     #[test]
     fn test_encode_uncompressed_latitude() {
         let mut buf = vec![];
-        Latitude(49.05833).encode_uncompressed(&mut buf).unwrap();
+        Latitude::new(49.05833)
+            .unwrap()
+            .encode_uncompressed(&mut buf)
+            .unwrap();
         assert_eq!(buf, &b"4903.50N"[..]);
 
         let mut buf = vec![];
-        Latitude(-49.05833).encode_uncompressed(&mut buf).unwrap();
+        Latitude::new(-49.05833)
+            .unwrap()
+            .encode_uncompressed(&mut buf)
+            .unwrap();
         assert_eq!(buf, &b"4903.50S"[..]);
 
         let mut buf = vec![];
         assert!(matches!(
-            Latitude(-90.1).encode_uncompressed(&mut buf),
+            invalid_latitude(-90.1).encode_uncompressed(&mut buf),
             Err(EncodeError::InvalidLatitude(x)) if x == -90.1
         ));
         assert!(matches!(
-            Latitude(90.1).encode_uncompressed(&mut buf),
+            invalid_latitude(90.1).encode_uncompressed(&mut buf),
             Err(EncodeError::InvalidLatitude(x)) if x == 90.1
         ));
 
         let mut buf = vec![];
-        Latitude(0.0).encode_uncompressed(&mut buf).unwrap();
+        Latitude::new(0.0)
+            .unwrap()
+            .encode_uncompressed(&mut buf)
+            .unwrap();
         assert_eq!(buf, &b"0000.00N"[..]);
     }
 
     #[test]
     fn test_encode_uncompressed_longitude() {
         let mut buf = vec![];
-        Longitude(129.05833).encode_uncompressed(&mut buf).unwrap();
+        Longitude::new(129.05833)
+            .unwrap()
+            .encode_uncompressed(&mut buf)
+            .unwrap();
         assert_eq!(buf, &b"12903.50E"[..]);
 
         let mut buf = vec![];
-        Longitude(-49.0583).encode_uncompressed(&mut buf).unwrap();
+        Longitude::new(-49.0583)
+            .unwrap()
+            .encode_uncompressed(&mut buf)
+            .unwrap();
         assert_eq!(buf, &b"04903.50W"[..]);
 
         let mut buf = vec![];
         assert!(matches!(
-            Longitude(-180.1).encode_uncompressed(&mut buf),
+            invalid_longitude(-180.1).encode_uncompressed(&mut buf),
             Err(EncodeError::InvalidLongitude(x)) if x == -180.1
         ));
         assert!(matches!(
-            Longitude(180.1).encode_uncompressed(&mut buf),
+            invalid_longitude(180.1).encode_uncompressed(&mut buf),
             Err(EncodeError::InvalidLongitude(x)) if x == 180.1
         ));
 
         let mut buf = vec![];
-        Longitude(0.0).encode_uncompressed(&mut buf).unwrap();
+        Longitude::new(0.0)
+            .unwrap()
+            .encode_uncompressed(&mut buf)
+            .unwrap();
         assert_eq!(buf, &b"00000.00E"[..]);
     }
+
+    #[test]
+    fn test_ambiguity() {
+        let lat = Latitude::parse_uncompressed(&b"4903.5 N"[..]).unwrap();
+        assert_eq!(lat.precision(), Precision::Uncompressed { ambiguity: 1 });
+        assert_relative_eq!(*lat, 49.0 + 3.55 / 60.0);
+
+        let lat = Latitude::parse_uncompressed(&b"49  .  S"[..]).unwrap();
+        assert_eq!(lat.precision(), Precision::Uncompressed { ambiguity: 4 });
+        assert_relative_eq!(*lat, -49.5);
+
+        // the latitude decides, digits in the longitude are ignored
+        let lon = Longitude::parse_uncompressed(&b"07201.75W"[..], 2).unwrap();
+        assert_eq!(lon.precision(), Precision::Uncompressed { ambiguity: 2 });
+        assert_relative_eq!(*lon, -(72.0 + 1.5 / 60.0));
+
+        assert!(Latitude::parse_uncompressed(&b"4   .  N"[..]).is_err());
+        assert!(Latitude::parse_uncompressed(&b"49 3.50N"[..]).is_err());
+        assert!(Longitude::parse_uncompressed(&b"072 1.75W"[..], 0).is_err());
+
+        for b in [&b"4903.5 N"[..], b"490 .  S", b"49  .  N", b"0000.00N"].iter() {
+            let mut buf = vec![];
+            Latitude::parse_uncompressed(b)
+                .unwrap()
+                .encode_uncompressed(&mut buf)
+                .unwrap();
+            assert_eq!(buf, *b);
+        }
+
+        // the digits hidden by the latitude are kept
+        let lon = Longitude::parse_uncompressed(&b"07201.75W"[..], 3).unwrap();
+        let mut buf = vec![];
+        lon.encode_uncompressed(&mut buf).unwrap();
+        assert_eq!(buf, &b"07201.75W"[..]);
+
+        let mut buf = vec![];
+        DecimalMinutes::from_degrees(-*lon, 3, b'W')
+            .encode(&mut buf, 3)
+            .unwrap();
+        assert_eq!(buf, &b"0720 .  W"[..]);
+    }
+
+    #[test]
+    fn test_reencode_exact_bytes() {
+        for b in [&b"0000.00S"[..], b"4860.00N", b"4899.99S"].iter() {
+            let mut buf = vec![];
+            Latitude::parse_uncompressed(b)
+                .unwrap()
+                .encode_uncompressed(&mut buf)
+                .unwrap();
+            assert_eq!(buf, *b);
+        }
+
+        for b in [&b"00000.00W"[..], b"01260.00E", b"18000.00W"].iter() {
+            let mut buf = vec![];
+            Longitude::parse_uncompressed(b, 0)
+                .unwrap()
+                .encode_uncompressed(&mut buf)
+                .unwrap();
+            assert_eq!(buf, *b);
+        }
+    }
+
+    #[test]
+    fn test_reencode_all_minutes() {
+        let mut buf = vec![];
+
+        for deg in 0..180 {
+            for hundredths in 0..6000 {
+                let min = hundredths / 100;
+                let min_frac = hundredths % 100;
+
+                for dir in [b'E', b'W'].iter() {
+                    let b = format!("{:03}{:02}.{:02}{}", deg, min, min_frac, *dir as char);
+                    let lon = Longitude::parse_uncompressed(b.as_bytes(), 0).unwrap();
+
+                    buf.clear();
+                    lon.encode_uncompressed(&mut buf).unwrap();
+                    assert_eq!(buf, b.as_bytes(), "{}", b);
+
+                    // from the float alone, without the original digits
+                    buf.clear();
+                    Longitude::new(*lon)
+                        .unwrap()
+                        .encode_uncompressed(&mut buf)
+                        .unwrap();
+                    assert_eq!(buf, b.as_bytes(), "{}", b);
+
+                    if deg < 90 {
+                        let b = &b[1..];
+                        let dir = if *dir == b'E' { "N" } else { "S" };
+                        let b = [&b[..b.len() - 1], dir].concat();
+                        let lat = Latitude::parse_uncompressed(b.as_bytes()).unwrap();
+
+                        buf.clear();
+                        lat.encode_uncompressed(&mut buf).unwrap();
+                        assert_eq!(buf, b.as_bytes(), "{}", b);
+
+                        buf.clear();
+                        Latitude::new(*lat)
+                            .unwrap()
+                            .encode_uncompressed(&mut buf)
+                            .unwrap();
+                        assert_eq!(buf, b.as_bytes(), "{}", b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolution() {
+        assert_eq!(Latitude::new(49.0).unwrap().resolution(), None);
+
+        let lat = Latitude::parse_uncompressed(&b"4903.50N"[..]).unwrap();
+        assert_relative_eq!(lat.resolution().unwrap().degrees(), 1.0 / 6000.0);
+
+        let lon = Longitude::parse_uncompressed(&b"0720 .  W"[..], 0).unwrap();
+        assert_relative_eq!(lon.resolution().unwrap().degrees(), 1.0 / 6.0);
+
+        let lat = Latitude::parse_compressed(&b"5L!!"[..]).unwrap();
+        assert_eq!(lat.precision(), Precision::Compressed);
+        assert_relative_eq!(lat.resolution().unwrap().degrees(), 1.0 / 380926.0);
+    }
+
+    #[test]
+    fn test_eq_includes_precision() {
+        let parsed = Latitude::parse_uncompressed(&b"4930.00N"[..]).unwrap();
+        assert_ne!(parsed, Latitude::new(49.5).unwrap());
+        assert_eq!(*parsed, *Latitude::new(49.5).unwrap());
+        assert_eq!(parsed.partial_cmp(&Latitude::new(49.5).unwrap()), None);
+        assert!(parsed < Latitude::new(50.0).unwrap());
+
+        // same value, but encoded differently
+        let a = Longitude::parse_uncompressed(&b"07201.75W"[..], 2).unwrap();
+        let b = Longitude::parse_uncompressed(&b"07201.  W"[..], 2).unwrap();
+        assert_eq!(*a, *b);
+        assert_ne!(a, b);
+        assert_eq!(b, Longitude::parse_uncompressed(&b"07201.  W"[..], 0).unwrap());
+    }
+
+    #[test]
+    fn test_dao() {
+        assert_eq!(Dao::find(b"comment"), None);
+        assert_eq!(Dao::find(b"!W0x!"), None);
+        assert_eq!(Dao::find(b"!w} !"), None);
+
+        let dao = Dao::find(b"!W12! id213D17F2 !W34!").unwrap();
+        let lat = Latitude::parse_uncompressed(&b"4903.50S"[..])
+            .unwrap()
+            .refine(&dao);
+        assert_relative_eq!(*lat, -(49.0 + 3.503 / 60.0));
+        assert_eq!(lat.precision(), Precision::Dao { base91: false });
+        assert_relative_eq!(lat.resolution().unwrap().degrees(), 0.001 / 60.0);
+        let lon = Longitude::parse_uncompressed(&b"07201.75E"[..], 0)
+            .unwrap()
+            .refine(&dao);
+        assert_relative_eq!(*lon, 72.0 + 1.754 / 60.0);
+
+        let dao = Dao::find(b"!w\"{!").unwrap();
+        let lat = Latitude::parse_uncompressed(&b"4903.50N"[..])
+            .unwrap()
+            .refine(&dao);
+        assert_relative_eq!(*lat, 49.0 + (3.50 + 1.0 / 9100.0) / 60.0);
+        assert_eq!(lat.precision(), Precision::Dao { base91: true });
+        let lon = Longitude::parse_uncompressed(&b"07201.75W"[..], 0)
+            .unwrap()
+            .refine(&dao);
+        assert_relative_eq!(*lon, -(72.0 + (1.75 + 90.0 / 9100.0) / 60.0));
+
+        // the original digits are encoded, the extension stays in the comment
+        let mut buf = vec![];
+        lat.encode_uncompressed(&mut buf).unwrap();
+        assert_eq!(buf, &b"4903.50N"[..]);
+
+        // ambiguous, compressed and out of range coordinates aren't refined
+        let lat = Latitude::parse_uncompressed(&b"4903.5 N"[..]).unwrap();
+        assert_eq!(lat.refine(&dao), lat);
+        let lat = Latitude::parse_compressed(&b"5L!!"[..]).unwrap();
+        assert_eq!(lat.refine(&dao), lat);
+        let lat = Latitude::parse_uncompressed(&b"9000.00N"[..]).unwrap();
+        assert_eq!(lat.refine(&dao), lat);
+    }
+
+    #[test]
+    fn test_unsupported_precision() {
+        let mut buf = vec![];
+        let lat = Latitude::parse_compressed(&b"5L!!"[..]).unwrap();
+        assert!(matches!(
+            lat.encode_uncompressed(&mut buf),
+            Err(EncodeError::UnsupportedPrecision(Precision::Compressed))
+        ));
+        assert!(lat.encode_compressed(&mut buf).is_ok());
+
+        let lon = Longitude::parse_uncompressed(&b"0720 .  W"[..], 0).unwrap();
+        assert!(matches!(
+            lon.encode_compressed(&mut buf),
+            Err(EncodeError::UnsupportedPrecision(Precision::Uncompressed {
+                ambiguity: 3
+            }))
+        ));
+
+        // finer than uncompressed, so nothing is lost
+        let lon = Longitude::parse_uncompressed(&b"07201.75W"[..], 0).unwrap();
+        assert!(lon.encode_compressed(&mut buf).is_ok());
+        let lon = lon.refine(&Dao::find(b"!W00!").unwrap());
+        assert!(matches!(
+            lon.encode_compressed(&mut buf),
+            Err(EncodeError::UnsupportedPrecision(Precision::Dao { .. }))
+        ));
+    }
 }
@@ -1,18 +1,80 @@
-use std::convert::TryFrom;
-use std::io::Write;
+use alloc::borrow::ToOwned;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::time::Duration;
 
 use AprsError;
+use Callsign;
+use Clock;
 use EncodeError;
+use EncodeWrite;
+
+/// Longest message text allowed by the spec, in bytes
+pub const MAX_MESSAGE_TEXT_LENGTH: usize = 67;
+
+// limits the memory a sender can make the reassembler allocate
+const MAX_PARTS: usize = 999;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AprsMessage {
+    #[cfg_attr(feature = "serde", serde(with = "::serde_support::bytes"))]
     pub addressee: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "::serde_support::bytes"))]
     pub text: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "::serde_support::option_bytes"))]
     pub id: Option<Vec<u8>>,
 }
 
 impl AprsMessage {
-    pub fn encode<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
+    /// Splits `text` into messages of at most [`MAX_MESSAGE_TEXT_LENGTH`]
+    /// bytes, numbered from `first_id`. Parts are split between words where
+    /// possible and end with a marker like `(1/3)`, which
+    /// [`MessageReassembler`] uses to put them together again. Text that
+    /// fits into one message is sent without marker, more than 999 parts
+    /// are refused.
+    pub fn split<T: AsRef<[u8]>>(
+        addressee: T,
+        text: &str,
+        first_id: u32,
+    ) -> Result<Vec<Self>, EncodeError> {
+        let addressee = addressee.as_ref();
+        if addressee.len() > 9 {
+            return Err(EncodeError::InvalidMessageAddressee(addressee.to_owned()));
+        }
+
+        let mut id = first_id;
+        Ok(split_text(text)?
+            .into_iter()
+            .map(|text| {
+                let message = Self {
+                    addressee: addressee.to_owned(),
+                    text: text.into_bytes(),
+                    id: Some(id.to_string().into_bytes()),
+                };
+                id = id % 99_999 + 1;
+                message
+            })
+            .collect())
+    }
+
+    /// Checks text for outgoing messages, which must not contain `|`, `~`,
+    /// `{` or line breaks and must fit into [`MAX_MESSAGE_TEXT_LENGTH`].
+    /// Longer text has to be split with [`AprsMessage::split`].
+    pub fn validate_text(text: &[u8]) -> Result<(), EncodeError> {
+        validate_chars(text)?;
+        if text.len() > MAX_MESSAGE_TEXT_LENGTH {
+            return Err(EncodeError::MessageTextTooLong(text.len()));
+        }
+
+        Ok(())
+    }
+
+    /// Doesn't check the text, so received messages are written as they
+    /// came in, see [`AprsMessage::validate_text`]
+    pub fn encode<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
         if self.addressee.len() > 9 {
             return Err(EncodeError::InvalidMessageAddressee(
                 self.addressee.to_owned(),
@@ -41,10 +103,36 @@ impl TryFrom<&[u8]> for AprsMessage {
     type Error = AprsError;
 
     fn try_from(b: &[u8]) -> Result<Self, AprsError> {
+        AprsMessageRef::try_from(b).map(AprsMessageRef::into_owned)
+    }
+}
+
+/// Borrowed version of [`AprsMessage`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AprsMessageRef<'a> {
+    pub addressee: &'a [u8],
+    pub text: &'a [u8],
+    pub id: Option<&'a [u8]>,
+}
+
+impl<'a> AprsMessageRef<'a> {
+    pub fn into_owned(self) -> AprsMessage {
+        AprsMessage {
+            addressee: self.addressee.to_vec(),
+            text: self.text.to_vec(),
+            id: self.id.map(|id| id.to_vec()),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for AprsMessageRef<'a> {
+    type Error = AprsError;
+
+    fn try_from(b: &'a [u8]) -> Result<Self, AprsError> {
         let mut splitter = b.splitn(2, |x| *x == b':');
 
-        let mut addressee = match splitter.next() {
-            Some(x) => x.to_vec(),
+        let addressee = match splitter.next() {
+            Some(x) => x,
             None => {
                 return Err(AprsError::InvalidMessageDestination(vec![]));
             }
@@ -54,12 +142,12 @@ impl TryFrom<&[u8]> for AprsMessage {
             return Err(AprsError::InvalidMessageDestination(addressee.to_owned()));
         }
 
-        trim_spaces_end(&mut addressee);
+        let addressee = trim_spaces_end(addressee);
 
         let text = splitter.next().unwrap_or(&[]);
         let mut text_splitter = text.splitn(2, |x| *x == b'{');
-        let text = text_splitter.next().unwrap_or(&[]).to_vec();
-        let id = text_splitter.next().map(|x| x.to_vec());
+        let text = text_splitter.next().unwrap_or(&[]);
+        let id = text_splitter.next();
 
         Ok(Self {
             addressee,
@@ -69,15 +157,333 @@ impl TryFrom<&[u8]> for AprsMessage {
     }
 }
 
-fn trim_spaces_end(arr: &mut Vec<u8>) {
+/// Puts messages split by [`AprsMessage::split`] together again, per
+/// sender
+///
+/// Parts that don't complete a message in time are handed out by
+/// [`MessageReassembler::poll_expired`] as they were received, so texts that
+/// only look like a part, e.g. `pick one (1/2)`, aren't lost. Copies of
+/// parts of a completed message, which are sent again when an ack gets
+/// lost, are dropped for the timeout after it completed.
+///
+/// ```rust
+/// # extern crate aprs_parser;
+/// # use std::time::Duration;
+/// # use aprs_parser::{AprsMessage, Callsign, ManualClock, MessageReassembler};
+/// let text = "This text is too long for a single message, so it is sent in two parts";
+/// let parts = AprsMessage::split("DEST", text, 1).unwrap();
+/// assert_eq!(parts[1].text, b"two parts (2/2)");
+///
+/// let clock = ManualClock::new();
+/// let mut reassembler = MessageReassembler::new(&clock);
+/// let from = Callsign::new("N0CALL", None);
+/// assert_eq!(reassembler.push(&from, &parts[0]), None);
+/// assert_eq!(reassembler.push(&from, &parts[1]), Some(text.as_bytes().to_vec()));
+///
+/// // a copy sent again because the ack got lost
+/// assert_eq!(reassembler.push(&from, &parts[1]), None);
+///
+/// let part = AprsMessage::split("DEST", &text.repeat(2), 3).unwrap().remove(0);
+/// assert_eq!(reassembler.push(&from, &part), None);
+/// clock.advance(Duration::from_secs(120));
+/// assert_eq!(reassembler.poll_expired(), Some((from, part.text)));
+/// assert_eq!(reassembler.poll_expired(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessageReassembler<C> {
+    clock: C,
+    timeout: Duration,
+    capacity: usize,
+    // incomplete messages by sender
+    pending: BTreeMap<String, PendingParts>,
+    expired: VecDeque<(Callsign, Vec<u8>)>,
+    // sender and parts of recently completed messages, oldest first
+    completed: VecDeque<CompletedParts>,
+}
+
+#[derive(Debug, Clone)]
+struct CompletedParts {
+    key: String,
+    finished: Duration,
+    parts: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone)]
+struct PendingParts {
+    from: Callsign,
+    started: Duration,
+    // received texts, including the marker
+    parts: Vec<Option<Vec<u8>>>,
+}
+
+impl<C: Clock> MessageReassembler<C> {
+    /// Gives up on messages that aren't complete 2 minutes after their first
+    /// part and keeps up to 100 incomplete messages
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            timeout: Duration::from_secs(120),
+            capacity: 100,
+            pending: BTreeMap::new(),
+            expired: VecDeque::new(),
+            completed: VecDeque::new(),
+        }
+    }
+
+    /// How long after its first part a message has to be complete
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Maximum number of incomplete messages, the oldest expire first
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// The complete text once all parts from the sender are in, messages
+    /// without a part marker are returned as they are. A part of a message
+    /// with a different number of parts starts over, the parts received so
+    /// far expire.
+    pub fn push(&mut self, from: &Callsign, message: &AprsMessage) -> Option<Vec<u8>> {
+        let now = self.clock.now();
+        self.expire(now);
+
+        let (part, total) = match split_marker(&message.text) {
+            Some((_, part, total)) => (part, total),
+            None => return Some(message.text.clone()),
+        };
+
+        let key = from.to_string();
+        if self
+            .completed
+            .iter()
+            .any(|completed| completed.key == key && completed.parts.contains(&message.text))
+        {
+            return None;
+        }
+
+        if let Some(pending) = self.pending.get(&key) {
+            if pending.parts.len() != total {
+                self.release(&key);
+            }
+        }
+
+        if !self.pending.contains_key(&key) && self.pending.len() >= self.capacity {
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|(_, pending)| pending.started)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.release(&oldest);
+            }
+        }
+
+        let pending = self
+            .pending
+            .entry(key.clone())
+            .or_insert_with(|| PendingParts {
+                from: from.clone(),
+                started: now,
+                parts: vec![None; total],
+            });
+        pending.parts[part - 1] = Some(message.text.clone());
+
+        if pending.parts.iter().any(Option::is_none) {
+            return None;
+        }
+
+        let parts: Vec<Vec<u8>> = self
+            .pending
+            .remove(&key)?
+            .parts
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut text = Vec::new();
+        for part in &parts {
+            if let Some((part, _, _)) = split_marker(part) {
+                text.extend_from_slice(part);
+            }
+        }
+
+        if self.completed.len() >= self.capacity {
+            self.completed.pop_front();
+        }
+        self.completed.push_back(CompletedParts {
+            key,
+            finished: now,
+            parts,
+        });
+
+        Some(text)
+    }
+
+    /// The next part of a message that wasn't completed in time, with its
+    /// marker. Parts of the same message are returned in order.
+    pub fn poll_expired(&mut self) -> Option<(Callsign, Vec<u8>)> {
+        let now = self.clock.now();
+        self.expire(now);
+
+        self.expired.pop_front()
+    }
+
+    /// When the oldest incomplete message expires, `None` if there is none
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.pending
+            .values()
+            .map(|pending| pending.started + self.timeout)
+            .min()
+    }
+
+    /// Drops incomplete and expired messages and forgets completed ones
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.expired.clear();
+        self.completed.clear();
+    }
+
+    fn expire(&mut self, now: Duration) {
+        let expired: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now >= pending.started + self.timeout)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in expired {
+            self.release(&key);
+        }
+
+        while let Some(completed) = self.completed.front() {
+            if now < completed.finished + self.timeout {
+                break;
+            }
+            self.completed.pop_front();
+        }
+    }
+
+    fn release(&mut self, key: &str) {
+        if let Some(pending) = self.pending.remove(key) {
+            for text in pending.parts.into_iter().flatten() {
+                self.expired.push_back((pending.from.clone(), text));
+            }
+        }
+    }
+}
+
+// Parts split between words end with ` (i/n)`, replacing the space, parts
+// split within a word with `(i/n)`. The last one always has a space.
+fn split_text(text: &str) -> Result<Vec<String>, EncodeError> {
+    validate_chars(text.as_bytes())?;
+
+    if text.len() <= MAX_MESSAGE_TEXT_LENGTH {
+        return Ok(vec![text.to_owned()]);
+    }
+
+    // the marker length depends on the number of parts
+    let mut total_digits = 1;
+    loop {
+        let max_marker = 4 + 2 * total_digits;
+        let budget = MAX_MESSAGE_TEXT_LENGTH - max_marker;
+
+        let mut chunks = vec![];
+        let mut rest = text;
+        while rest.len() > budget {
+            let mut end = budget;
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let space = rest.as_bytes()[..=end].iter().rposition(|b| *b == b' ');
+            match space.filter(|i| *i > 0) {
+                Some(space) => {
+                    chunks.push((&rest[..space], true));
+                    rest = &rest[space + 1..];
+                }
+                None => {
+                    chunks.push((&rest[..end], false));
+                    rest = &rest[end..];
+                }
+            }
+        }
+        chunks.push((rest, true));
+
+        let total = chunks.len();
+        if total > MAX_PARTS {
+            return Err(EncodeError::InvalidMessageText(text.as_bytes().to_owned()));
+        }
+        if total.to_string().len() > total_digits {
+            total_digits += 1;
+            continue;
+        }
+
+        return Ok(chunks
+            .into_iter()
+            .enumerate()
+            .map(|(i, (chunk, space))| {
+                let space = if space { " " } else { "" };
+                format!("{}{}({}/{})", chunk, space, i + 1, total)
+            })
+            .collect());
+    }
+}
+
+fn validate_chars(text: &[u8]) -> Result<(), EncodeError> {
+    if text
+        .iter()
+        .any(|b| matches!(b, b'|' | b'~' | b'{' | b'\r' | b'\n'))
+    {
+        return Err(EncodeError::InvalidMessageText(text.to_owned()));
+    }
+
+    Ok(())
+}
+
+// text without the marker, part and total
+fn split_marker(text: &[u8]) -> Option<(&[u8], usize, usize)> {
+    let (rest, marker) = match text.split_last()? {
+        (b')', rest) => {
+            let start = rest.iter().rposition(|b| *b == b'(')?;
+            (&rest[..start], &rest[start + 1..])
+        }
+        _ => return None,
+    };
+
+    let slash = marker.iter().position(|b| *b == b'/')?;
+    let number = |b: &[u8]| -> Option<usize> {
+        if b.is_empty() || !b.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        core::str::from_utf8(b).ok()?.parse().ok()
+    };
+    let part = number(&marker[..slash])?;
+    let total = number(&marker[slash + 1..])?;
+    if total < 2 || total > MAX_PARTS || part == 0 || part > total {
+        return None;
+    }
+
+    // the space before the last marker belongs to it
+    let rest = match rest.split_last() {
+        Some((b' ', text)) if part == total => text,
+        _ => rest,
+    };
+
+    Some((rest, part, total))
+}
+
+fn trim_spaces_end(arr: &[u8]) -> &[u8] {
     let space_count = arr.iter().rev().take_while(|&&b| b == b' ').count();
 
-    arr.truncate(arr.len() - space_count);
+    &arr[..arr.len() - space_count]
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ManualClock;
 
     #[test]
     fn parse_message_invalid_dest() {
@@ -136,4 +542,221 @@ mod tests {
             })
         );
     }
+
+    fn texts(parts: &[AprsMessage]) -> Vec<&str> {
+        parts
+            .iter()
+            .map(|part| core::str::from_utf8(&part.text).unwrap())
+            .collect()
+    }
+
+    fn reassemble(parts: &[AprsMessage]) -> Vec<u8> {
+        let from = Callsign::new("N0CALL", None);
+        let mut reassembler = MessageReassembler::new(ManualClock::new());
+        let (last, parts) = parts.split_last().unwrap();
+        for part in parts {
+            assert_eq!(reassembler.push(&from, part), None);
+        }
+        reassembler.push(&from, last).unwrap()
+    }
+
+    #[test]
+    fn split_short() {
+        let parts = AprsMessage::split("DEST", "Hello", 42).unwrap();
+        assert_eq!(
+            parts,
+            vec![AprsMessage {
+                addressee: b"DEST".to_vec(),
+                text: b"Hello".to_vec(),
+                id: Some(b"42".to_vec()),
+            }]
+        );
+
+        let text = "x".repeat(MAX_MESSAGE_TEXT_LENGTH);
+        assert_eq!(AprsMessage::split("DEST", &text, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn split_words() {
+        let text = "The quick brown fox jumps over the lazy dog. The quick brown fox jumps over the lazy dog.";
+        let parts = AprsMessage::split("DEST", text, 99_999).unwrap();
+
+        assert_eq!(
+            texts(&parts),
+            vec![
+                "The quick brown fox jumps over the lazy dog. The quick brown (1/2)",
+                "fox jumps over the lazy dog. (2/2)",
+            ]
+        );
+        assert_eq!(parts[0].id, Some(b"99999".to_vec()));
+        assert_eq!(parts[1].id, Some(b"1".to_vec()));
+        assert_eq!(reassemble(&parts), text.as_bytes());
+    }
+
+    #[test]
+    fn split_long_words() {
+        let text = format!("{} {}", "ä".repeat(40), "b".repeat(100));
+        let parts = AprsMessage::split("DEST", &text, 1).unwrap();
+
+        for part in &parts {
+            assert!(part.text.len() <= MAX_MESSAGE_TEXT_LENGTH);
+        }
+        assert_eq!(parts.len(), 4);
+        assert_eq!(texts(&parts)[0], format!("{}(1/4)", "ä".repeat(30)));
+        assert_eq!(reassemble(&parts), text.as_bytes());
+
+        // the marker grows with the number of parts
+        let text = "word ".repeat(500);
+        let parts = AprsMessage::split("DEST", &text, 1).unwrap();
+        assert!(parts.len() > 9);
+        for part in &parts {
+            assert!(part.text.len() <= MAX_MESSAGE_TEXT_LENGTH);
+        }
+        assert!(texts(&parts)[0].ends_with(&format!(" (1/{})", parts.len())));
+        assert_eq!(reassemble(&parts), text.as_bytes());
+    }
+
+    #[test]
+    fn split_invalid() {
+        assert!(matches!(
+            AprsMessage::split("DEST", "a|b", 1),
+            Err(EncodeError::InvalidMessageText(x)) if x == b"a|b"
+        ));
+        assert!(AprsMessage::split("DEST", "~", 1).is_err());
+        assert!(AprsMessage::split("DEST", "Hello{1", 1).is_err());
+        assert!(AprsMessage::split("DEST", "line\nbreak", 1).is_err());
+        assert!(AprsMessage::split("DESTINATION", "Hello", 1).is_err());
+        assert!(AprsMessage::split("DEST", &"x".repeat(70_000), 1).is_err());
+
+        assert!(AprsMessage::validate_text(b"Hello, World! (1/2)").is_ok());
+        assert!(AprsMessage::validate_text(&[b'x'; MAX_MESSAGE_TEXT_LENGTH]).is_ok());
+        assert!(matches!(
+            AprsMessage::validate_text(&[b'x'; MAX_MESSAGE_TEXT_LENGTH + 1]),
+            Err(EncodeError::MessageTextTooLong(68))
+        ));
+    }
+
+    #[test]
+    fn reassemble_interleaved() {
+        let a = Callsign::new("AAA", None);
+        let b = Callsign::new("BBB", None);
+        let part = |text: &str| AprsMessage {
+            addressee: b"DEST".to_vec(),
+            text: text.as_bytes().to_vec(),
+            id: None,
+        };
+
+        let clock = ManualClock::new();
+        let mut reassembler = MessageReassembler::new(&clock);
+        assert_eq!(reassembler.push(&a, &part("two (2/2)")), None);
+        assert_eq!(reassembler.push(&b, &part("one (1/3)")), None);
+        assert_eq!(
+            reassembler.push(&a, &part("no marker (x/2)")),
+            Some(b"no marker (x/2)".to_vec())
+        );
+        assert_eq!(
+            reassembler.push(&a, &part("one (1/2)")),
+            Some(b"one two".to_vec())
+        );
+
+        // a different number of parts starts over
+        assert_eq!(reassembler.push(&b, &part("one (1/2)")), None);
+        assert_eq!(
+            reassembler.poll_expired(),
+            Some((b.clone(), b"one (1/3)".to_vec()))
+        );
+        assert_eq!(reassembler.push(&b, &part("three (3/3)")), None);
+        assert_eq!(
+            reassembler.poll_expired(),
+            Some((b.clone(), b"one (1/2)".to_vec()))
+        );
+        reassembler.clear();
+        assert_eq!(reassembler.push(&b, &part("two (2/2)")), None);
+        assert_eq!(reassembler.poll_expired(), None);
+    }
+
+    #[test]
+    fn reassemble_expiry() {
+        let a = Callsign::new("AAA", None);
+        let b = Callsign::new("BBB", None);
+        let part = |text: &str| AprsMessage {
+            addressee: b"DEST".to_vec(),
+            text: text.as_bytes().to_vec(),
+            id: None,
+        };
+
+        let clock = ManualClock::new();
+        let mut reassembler = MessageReassembler::new(&clock).timeout(Duration::from_secs(60));
+        assert_eq!(reassembler.next_deadline(), None);
+
+        assert_eq!(reassembler.push(&a, &part("pick one (1/2)")), None);
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(reassembler.push(&b, &part("three (3/3)")), None);
+        assert_eq!(reassembler.push(&b, &part("one (1/3)")), None);
+        assert_eq!(reassembler.next_deadline(), Some(Duration::from_secs(60)));
+        assert_eq!(reassembler.poll_expired(), None);
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            reassembler.poll_expired(),
+            Some((a.clone(), b"pick one (1/2)".to_vec()))
+        );
+        assert_eq!(reassembler.poll_expired(), None);
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            reassembler.poll_expired(),
+            Some((b.clone(), b"one (1/3)".to_vec()))
+        );
+        assert_eq!(
+            reassembler.poll_expired(),
+            Some((b.clone(), b"three (3/3)".to_vec()))
+        );
+        assert_eq!(reassembler.poll_expired(), None);
+        assert_eq!(reassembler.next_deadline(), None);
+
+        // the oldest incomplete message expires to make room
+        let mut reassembler = MessageReassembler::new(&clock).capacity(1);
+        assert_eq!(reassembler.push(&a, &part("one (1/2)")), None);
+        assert_eq!(reassembler.push(&b, &part("one (1/2)")), None);
+        assert_eq!(reassembler.poll_expired(), Some((a, b"one (1/2)".to_vec())));
+        assert_eq!(
+            reassembler.push(&b, &part("two (2/2)")),
+            Some(b"one two".to_vec())
+        );
+    }
+
+    #[test]
+    fn reassemble_retransmit() {
+        let from = Callsign::new("N0CALL", None);
+        let parts = AprsMessage::split("DEST", &"a long message ".repeat(6), 1).unwrap();
+        assert_eq!(parts.len(), 2);
+
+        let clock = ManualClock::new();
+        let mut reassembler = MessageReassembler::new(&clock);
+        assert_eq!(reassembler.push(&from, &parts[0]), None);
+        assert!(reassembler.push(&from, &parts[1]).is_some());
+
+        // copies of both parts after the message completed
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(reassembler.push(&from, &parts[1]), None);
+        assert_eq!(reassembler.push(&from, &parts[0]), None);
+        assert_eq!(reassembler.next_deadline(), None);
+        clock.advance(Duration::from_secs(121));
+        assert_eq!(reassembler.poll_expired(), None);
+
+        // another sender's parts aren't copies
+        let other = Callsign::new("N1CALL", None);
+        assert_eq!(reassembler.push(&other, &parts[0]), None);
+        assert!(reassembler.push(&other, &parts[1]).is_some());
+
+        // once the timeout has passed the same text counts as a new message
+        clock.advance(Duration::from_secs(120));
+        assert_eq!(reassembler.push(&from, &parts[1]), None);
+        clock.advance(Duration::from_secs(120));
+        assert_eq!(
+            reassembler.poll_expired(),
+            Some((from, parts[1].text.clone()))
+        );
+    }
 }
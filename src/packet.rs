@@ -1,13 +1,26 @@
-use std::convert::TryFrom;
-use std::io::Write;
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
 
+use callsign::CallsignRef;
+use encode::{CountingWriter, SliceWriter};
+use message::AprsMessageRef;
+use position::AprsPositionRef;
+use user_defined::AprsUserDefinedRef;
 use AprsError;
 use AprsMessage;
 use AprsPosition;
+use AprsUserDefined;
 use Callsign;
 use EncodeError;
+use EncodeWrite;
+use ParseError;
+use ParseField;
+use ParseWarning;
+use Timestamp;
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AprsPacket {
     pub from: Callsign,
     pub to: Callsign,
@@ -19,46 +32,27 @@ impl TryFrom<&[u8]> for AprsPacket {
     type Error = AprsError;
 
     fn try_from(s: &[u8]) -> Result<Self, Self::Error> {
-        let header_delimiter = s
-            .iter()
-            .position(|x| *x == b':')
-            .ok_or_else(|| AprsError::InvalidPacket(s.to_owned()))?;
-        let (header, rest) = s.split_at(header_delimiter);
-        let body = &rest[1..];
-
-        let from_delimiter = header
-            .iter()
-            .position(|x| *x == b'>')
-            .ok_or_else(|| AprsError::InvalidPacket(s.to_owned()))?;
-        let (from, rest) = header.split_at(from_delimiter);
-        let from = Callsign::try_from(from)?;
-
-        let to_and_via = &rest[1..];
-        let mut to_and_via = to_and_via.split(|x| *x == b',');
-
-        let to = to_and_via
-            .next()
-            .ok_or_else(|| AprsError::InvalidPacket(s.to_owned()))?;
-        let to = Callsign::try_from(to)?;
+        AprsPacketRef::try_from(s).map(AprsPacketRef::into_owned)
+    }
+}
 
-        let mut via = vec![];
-        for v in to_and_via {
-            via.push(Callsign::try_from(v)?);
-        }
+impl AprsPacket {
+    /// Owned version of [`AprsPacketRef::parse_lenient`]
+    pub fn parse_lenient(s: &[u8]) -> Result<(Self, Vec<ParseWarning>), AprsError> {
+        AprsPacketRef::parse_lenient(s).map(|(packet, warnings)| (packet.into_owned(), warnings))
+    }
 
-        let data = AprsData::try_from(body)?;
+    /// Owned version of [`AprsPacketRef::parse_detailed`]
+    pub fn parse_detailed(s: &[u8]) -> Result<Self, ParseError> {
+        AprsPacketRef::parse_detailed(s).map(AprsPacketRef::into_owned)
+    }
 
-        Ok(AprsPacket {
-            from,
-            to,
-            via,
-            data,
-        })
+    /// Owned version of [`AprsPacketRef::parse_strict`]
+    pub fn parse_strict(s: &[u8]) -> Result<Self, ParseError> {
+        AprsPacketRef::parse_strict(s).map(AprsPacketRef::into_owned)
     }
-}
 
-impl AprsPacket {
-    pub fn encode<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
+    pub fn encode<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
         write!(buf, "{}>{}", self.from, self.to)?;
         for v in &self.via {
             write!(buf, ",{}", v)?;
@@ -68,29 +62,47 @@ impl AprsPacket {
 
         Ok(())
     }
+
+    /// Encodes into a fixed buffer without allocating and returns the number
+    /// of bytes written. Fails with [`EncodeError::BufferTooSmall`] and the
+    /// required length if the packet does not fit.
+    pub fn encode_to_slice(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let mut writer = SliceWriter::new(buf);
+        self.encode(&mut writer)?;
+        writer.finish()
+    }
+
+    /// Length of the encoded packet, computed without allocating
+    pub fn encoded_len(&self) -> Result<usize, EncodeError> {
+        let mut writer = CountingWriter(0);
+        self.encode(&mut writer)?;
+        Ok(writer.0)
+    }
 }
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AprsData {
     Position(AprsPosition),
     Message(AprsMessage),
-    Unknown,
+    UserDefined(AprsUserDefined),
+    /// Invalid data or test data (`,`)
+    TestData(#[cfg_attr(feature = "serde", serde(with = "::serde_support::bytes"))] Vec<u8>),
+    /// Unsupported data, the raw information field starting with the data
+    /// type identifier. Encoded verbatim.
+    Unknown(#[cfg_attr(feature = "serde", serde(with = "::serde_support::bytes"))] Vec<u8>),
 }
 
 impl TryFrom<&[u8]> for AprsData {
     type Error = AprsError;
 
     fn try_from(s: &[u8]) -> Result<Self, AprsError> {
-        Ok(match *s.first().unwrap_or(&0) {
-            b':' => AprsData::Message(AprsMessage::try_from(&s[1..])?),
-            b'!' | b'/' | b'=' | b'@' => AprsData::Position(AprsPosition::try_from(s)?),
-            _ => AprsData::Unknown,
-        })
+        AprsDataRef::try_from(s).map(AprsDataRef::into_owned)
     }
 }
 
 impl AprsData {
-    fn encode<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
+    pub(crate) fn encode<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
         match self {
             Self::Position(p) => {
                 p.encode(buf)?;
@@ -98,17 +110,226 @@ impl AprsData {
             Self::Message(m) => {
                 m.encode(buf)?;
             }
-            Self::Unknown => return Err(EncodeError::InvalidData),
+            Self::UserDefined(u) => {
+                u.encode(buf)?;
+            }
+            Self::TestData(data) => {
+                buf.write_all(b",")?;
+                buf.write_all(data)?;
+            }
+            Self::Unknown(data) => {
+                buf.write_all(data)?;
+            }
         }
 
         Ok(())
     }
 }
 
+/// Borrowed version of [`AprsPacket`]
+///
+/// Parsing into this type does not allocate (except for unsupported
+/// timestamps) and all byte fields point into the parsed buffer.
+#[derive(PartialEq, Debug, Clone)]
+pub struct AprsPacketRef<'a> {
+    pub from: CallsignRef<'a>,
+    pub to: CallsignRef<'a>,
+    /// Raw, already validated path after the destination
+    via: Option<&'a [u8]>,
+    pub data: AprsDataRef<'a>,
+}
+
+impl<'a> TryFrom<&'a [u8]> for AprsPacketRef<'a> {
+    type Error = AprsError;
+
+    fn try_from(s: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(s, None).map_err(|w| w.reason)
+    }
+}
+
+impl<'a> AprsPacketRef<'a> {
+    /// Parses a packet, skipping or replacing malformed fields after the
+    /// header instead of failing:
+    ///
+    /// - invalid callsigns in the path are skipped
+    /// - invalid timestamps are kept as [`Timestamp::Unsupported`]
+    /// - invalid compressed course/speed, radio range or altitude is dropped
+    /// - data that still fails to parse is kept as [`AprsDataRef::Unknown`]
+    ///
+    /// Only an invalid header is an error.
+    ///
+    /// [`Timestamp::Unsupported`]: crate::Timestamp::Unsupported
+    pub fn parse_lenient(s: &'a [u8]) -> Result<(Self, Vec<ParseWarning>), AprsError> {
+        let mut warnings = vec![];
+        let packet = Self::parse(s, Some(&mut warnings)).map_err(|w| w.reason)?;
+
+        Ok((packet, warnings))
+    }
+
+    /// Parses a packet like `TryFrom`, but the error points to the bad
+    /// bytes in the input
+    pub fn parse_detailed(s: &'a [u8]) -> Result<Self, ParseError> {
+        Self::parse(s, None).map_err(|w| ParseError::new(w, s))
+    }
+
+    /// Like [`AprsPacketRef::parse_detailed`], but also rejects position
+    /// timestamps with out-of-range fields like `993499z`, see
+    /// [`Timestamp::validate`]
+    pub fn parse_strict(s: &'a [u8]) -> Result<Self, ParseError> {
+        let packet = Self::parse_detailed(s)?;
+
+        if let AprsDataRef::Position(position) = &packet.data {
+            if let Some(Err(e)) = position.timestamp.as_ref().map(Timestamp::validate) {
+                // the timestamp follows the data type identifier
+                let offset = s.iter().position(|x| *x == b':').unwrap_or(0) + 2;
+                let warning = ParseWarning::new(ParseField::Timestamp, offset, 7, e);
+                return Err(ParseError::new(warning, s));
+            }
+        }
+
+        Ok(packet)
+    }
+
+    fn parse(
+        s: &'a [u8],
+        mut warnings: Option<&mut Vec<ParseWarning>>,
+    ) -> Result<Self, ParseWarning> {
+        let invalid_header = |len| {
+            ParseWarning::new(
+                ParseField::Header,
+                0,
+                len,
+                AprsError::InvalidPacket(s.to_owned()),
+            )
+        };
+
+        let header_delimiter = s
+            .iter()
+            .position(|x| *x == b':')
+            .ok_or_else(|| invalid_header(s.len()))?;
+        let (header, rest) = s.split_at(header_delimiter);
+        let body = &rest[1..];
+
+        let from_delimiter = header
+            .iter()
+            .position(|x| *x == b'>')
+            .ok_or_else(|| invalid_header(header.len()))?;
+        let (from, rest) = header.split_at(from_delimiter);
+        let from = CallsignRef::try_from(from)
+            .map_err(|e| ParseWarning::new(ParseField::From, 0, from.len(), e))?;
+
+        let to_and_via = &rest[1..];
+        let (to, via) = match to_and_via.iter().position(|x| *x == b',') {
+            Some(i) => (&to_and_via[..i], Some(&to_and_via[i + 1..])),
+            None => (to_and_via, None),
+        };
+        let to = CallsignRef::try_from(to)
+            .map_err(|e| ParseWarning::new(ParseField::To, from_delimiter + 1, to.len(), e))?;
+
+        if let Some(via) = via {
+            let mut offset = header.len() - via.len();
+            for v in via.split(|x| *x == b',') {
+                if let Err(e) = CallsignRef::try_from(v) {
+                    ParseWarning::new(ParseField::Via, offset, v.len(), e)
+                        .recover(warnings.as_deref_mut())?;
+                }
+                offset += v.len() + 1;
+            }
+        }
+
+        let data = AprsDataRef::parse(body, header_delimiter + 1, warnings)?;
+
+        Ok(AprsPacketRef {
+            from,
+            to,
+            via,
+            data,
+        })
+    }
+
+    pub fn via(&self) -> impl Iterator<Item = CallsignRef<'a>> + 'a {
+        self.via
+            .into_iter()
+            .flat_map(|via| via.split(|x| *x == b','))
+            // validated while parsing, invalid ones are skipped in lenient mode
+            .filter_map(|v| CallsignRef::try_from(v).ok())
+    }
+
+    pub fn into_owned(self) -> AprsPacket {
+        AprsPacket {
+            from: self.from.into_owned(),
+            to: self.to.into_owned(),
+            via: self.via().map(CallsignRef::into_owned).collect(),
+            data: self.data.into_owned(),
+        }
+    }
+}
+
+/// Borrowed version of [`AprsData`]
+#[derive(PartialEq, Debug, Clone)]
+pub enum AprsDataRef<'a> {
+    Position(AprsPositionRef<'a>),
+    Message(AprsMessageRef<'a>),
+    UserDefined(AprsUserDefinedRef<'a>),
+    TestData(&'a [u8]),
+    Unknown(&'a [u8]),
+}
+
+impl<'a> TryFrom<&'a [u8]> for AprsDataRef<'a> {
+    type Error = AprsError;
+
+    fn try_from(s: &'a [u8]) -> Result<Self, AprsError> {
+        Self::parse(s, 0, None).map_err(|w| w.reason)
+    }
+}
+
+impl<'a> AprsDataRef<'a> {
+    // `offset` is the position of `s` in the packet
+    fn parse(
+        s: &'a [u8],
+        offset: usize,
+        mut warnings: Option<&mut Vec<ParseWarning>>,
+    ) -> Result<Self, ParseWarning> {
+        let data = match *s.first().unwrap_or(&0) {
+            b':' => AprsMessageRef::try_from(&s[1..])
+                .map(AprsDataRef::Message)
+                .map_err(|e| ParseWarning::new(ParseField::Message, offset, s.len(), e)),
+            b'!' | b'/' | b'=' | b'@' => AprsPositionRef::parse(s, offset, warnings.as_deref_mut())
+                .map(AprsDataRef::Position),
+            // not every `{` body follows the user-defined format
+            b'{' => Ok(AprsUserDefinedRef::try_from(&s[1..])
+                .map(AprsDataRef::UserDefined)
+                .unwrap_or(AprsDataRef::Unknown(s))),
+            b',' => Ok(AprsDataRef::TestData(&s[1..])),
+            _ => Ok(AprsDataRef::Unknown(s)),
+        };
+
+        match data {
+            Ok(data) => Ok(data),
+            Err(w) => {
+                w.recover(warnings)?;
+                Ok(AprsDataRef::Unknown(s))
+            }
+        }
+    }
+
+    pub fn into_owned(self) -> AprsData {
+        match self {
+            AprsDataRef::Position(p) => AprsData::Position(p.into_owned()),
+            AprsDataRef::Message(m) => AprsData::Message(m.into_owned()),
+            AprsDataRef::UserDefined(u) => AprsData::UserDefined(u.into_owned()),
+            AprsDataRef::TestData(data) => AprsData::TestData(data.to_vec()),
+            AprsDataRef::Unknown(data) => AprsData::Unknown(data.to_vec()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use Timestamp;
+    use AprsCst;
+    use Precision;
+    use TimestampField;
 
     #[test]
     fn parse() {
@@ -123,8 +344,13 @@ mod tests {
         match result.data {
             AprsData::Position(position) => {
                 assert_eq!(position.timestamp, Some(Timestamp::HHMMSS(7, 48, 49)));
+                // refined by `!W09!`
                 assert_relative_eq!(*position.latitude, 48.36016666666667);
-                assert_relative_eq!(*position.longitude, 12.408166666666666);
+                assert_relative_eq!(*position.longitude, 12.408316666666666);
+                assert_eq!(
+                    position.longitude.precision(),
+                    Precision::Dao { base91: false }
+                );
                 assert_eq!(
                     position.comment,
                     b"322/103/A=003054 !W09! id213D17F2 -039fpm +0.0rot 2.5dB 3e -0.0kHz gps1x1"
@@ -157,6 +383,216 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_user_defined_and_test_data() {
+        let result = AprsPacket::try_from(&b"N0CALL>APRS:{Q1abc"[..]).unwrap();
+        assert_eq!(
+            result.data,
+            AprsData::UserDefined(AprsUserDefined {
+                user_id: 'Q',
+                packet_type: '1',
+                data: b"abc".to_vec(),
+            })
+        );
+
+        let result = AprsPacketRef::try_from(&b"N0CALL>APRS:,\x00test"[..]).unwrap();
+        assert_eq!(result.data, AprsDataRef::TestData(b"\x00test"));
+
+        for b in [&b"{Q"[..], b"{Q abc"].iter() {
+            let mut packet = b"N0CALL>APRS:".to_vec();
+            packet.extend_from_slice(b);
+            let result = AprsPacket::try_from(&packet[..]).unwrap();
+            assert_eq!(result.data, AprsData::Unknown(b.to_vec()));
+        }
+    }
+
+    #[test]
+    fn parse_unknown() {
+        let result = AprsPacket::try_from(&b"N0CALL>APRS:>status \xff"[..]).unwrap();
+        assert_eq!(result.data, AprsData::Unknown(b">status \xff".to_vec()));
+
+        let result = AprsPacketRef::try_from(&b"N0CALL>APRS:"[..]).unwrap();
+        assert_eq!(result.data, AprsDataRef::Unknown(b""));
+    }
+
+    #[test]
+    fn parse_lenient() {
+        let fields = |warnings: &[ParseWarning]| {
+            warnings
+                .iter()
+                .map(|w| (w.field, w.offset))
+                .collect::<Vec<_>>()
+        };
+
+        let b = &b"N0CALL>APRS:!/5L!!<*e7>7P  comment"[..];
+        let (packet, warnings) = AprsPacket::parse_lenient(b).unwrap();
+        assert_eq!(fields(&warnings), [(ParseField::CompressedCs, 23)]);
+        let mut buf = vec![];
+        packet.encode(&mut buf).unwrap();
+        assert_eq!(buf, b);
+        match packet.data {
+            AprsData::Position(position) => {
+                assert_eq!(position.cst, AprsCst::CompressedNone(*b"7P "));
+                assert_eq!(position.comment, b" comment");
+            }
+            _ => panic!("Unexpected data type"),
+        }
+        assert_eq!(
+            AprsPacket::try_from(b).err().as_ref(),
+            Some(&warnings[0].reason)
+        );
+
+        let b = &br"N0CALL>APRS,WIDE1-,WIDE2-2:/99x999h4821.61N\01224.49E^"[..];
+        let (packet, warnings) = AprsPacketRef::parse_lenient(b).unwrap();
+        assert_eq!(
+            warnings[0],
+            ParseWarning::new(
+                ParseField::Via,
+                12,
+                6,
+                AprsError::EmptySSID("WIDE1-".to_owned())
+            )
+        );
+        assert_eq!(fields(&warnings[1..]), [(ParseField::Timestamp, 28)]);
+        assert_eq!(packet.via().map(|v| v.call).collect::<Vec<_>>(), ["WIDE2"]);
+        match packet.data {
+            AprsDataRef::Position(position) => assert_eq!(
+                position.timestamp,
+                Some(Timestamp::Unsupported(b"99x999h".to_vec()))
+            ),
+            _ => panic!("Unexpected data type"),
+        }
+
+        let b = &br"N0CALL>APRS:!4x21.61N\01224.49E^"[..];
+        let (packet, warnings) = AprsPacketRef::parse_lenient(b).unwrap();
+        assert_eq!(fields(&warnings), [(ParseField::Latitude, 13)]);
+        assert_eq!(packet.data, AprsDataRef::Unknown(&b[12..]));
+
+        let b = &b"N0CALL>APRS::DEST:Hello"[..];
+        let (packet, warnings) = AprsPacketRef::parse_lenient(b).unwrap();
+        assert_eq!(fields(&warnings), [(ParseField::Message, 12)]);
+        assert_eq!(packet.data, AprsDataRef::Unknown(&b[12..]));
+
+        let (_, warnings) = AprsPacketRef::parse_lenient(&b"N0CALL>APRS:>status"[..]).unwrap();
+        assert_eq!(warnings, []);
+
+        assert_eq!(
+            AprsPacketRef::parse_lenient(&b"N0CALL-:>status"[..]),
+            Err(AprsError::InvalidPacket(b"N0CALL-:>status".to_vec()))
+        );
+    }
+
+    #[test]
+    fn parse_strict() {
+        let b = &br"N0CALL>APRS,WIDE2-2:/993499z4821.61N\01224.49E^"[..];
+        assert!(AprsPacket::parse_detailed(b).is_ok());
+
+        let e = AprsPacket::parse_strict(b).unwrap_err();
+        assert_eq!(e.field, ParseField::Timestamp);
+        assert_eq!(e.found(), b"993499z");
+        assert_eq!(
+            e.reason,
+            AprsError::TimestampOutOfRange {
+                field: TimestampField::Day,
+                value: 99
+            }
+        );
+
+        let b = &br"N0CALL>APRS:@092345z4821.61N\01224.49E^"[..];
+        assert_eq!(
+            AprsPacket::parse_strict(b).unwrap(),
+            AprsPacket::try_from(b).unwrap()
+        );
+        assert!(AprsPacket::parse_strict(&b"N0CALL>APRS:>status"[..]).is_ok());
+        assert_eq!(
+            AprsPacketRef::parse_strict(br"N0CALL>APRS:!4x21.61N\01224.49E^")
+                .unwrap_err()
+                .field,
+            ParseField::Latitude
+        );
+    }
+
+    #[test]
+    fn parse_detailed() {
+        let e = AprsPacket::parse_detailed(br"N0CALL>APRS:!4x21.61N\01224.49E^").unwrap_err();
+        assert_eq!(e.field, ParseField::Latitude);
+        assert_eq!((e.offset, e.len), (13, 8));
+        assert_eq!(e.found(), b"4x21.61N");
+        assert_eq!(
+            e.to_string().lines().collect::<Vec<_>>(),
+            [
+                "Invalid Latitude: [52, 120, 50, 49, 46, 54, 49, 78]",
+                "expected a latitude like 4903.50N or 4 base91 digits, found \"4x21.61N\"",
+                r"  N0CALL>APRS:!4x21.61N\01224.49E^",
+                "               ^^^^^^^^",
+            ]
+        );
+
+        let e = AprsPacketRef::parse_detailed(b"N0CALL>AP\xffRS,WIDE1-1:>status").unwrap_err();
+        assert_eq!(e.field, ParseField::To);
+        assert_eq!(e.found(), b"AP\xffRS");
+        assert!(e
+            .to_string()
+            .ends_with("\n  N0CALL>AP.RS,WIDE1-1:>status\n         ^^^^^"));
+
+        let e = AprsPacketRef::parse_detailed(b"N0CALL>APRS").unwrap_err();
+        assert_eq!((e.field, e.offset, e.len), (ParseField::Header, 0, 11));
+        assert_eq!(
+            AprsError::from(e),
+            AprsError::InvalidPacket(b"N0CALL>APRS".to_vec())
+        );
+
+        let e = AprsPacketRef::parse_detailed(b"N0CALL>APRS,WIDE1-1,-2:>status").unwrap_err();
+        assert_eq!((e.field, e.offset, e.len), (ParseField::Via, 20, 2));
+    }
+
+    #[test]
+    fn parse_borrowed() {
+        let b = &br"ICA3D17F2>APRS,qAS,dl4mea:/074849h4821.61N\01224.49E^322/103/A=003054"[..];
+        let result = AprsPacketRef::try_from(b).unwrap();
+        assert_eq!(result.from.call, "ICA3D17F2");
+        assert_eq!(result.to.call, "APRS");
+        assert_eq!(
+            result.via().map(|v| v.call).collect::<Vec<_>>(),
+            vec!["qAS", "dl4mea"]
+        );
+
+        match &result.data {
+            AprsDataRef::Position(position) => {
+                assert_eq!(position.comment, b"322/103/A=003054");
+            }
+            _ => panic!("Unexpected data type"),
+        }
+
+        assert_eq!(result.into_owned(), AprsPacket::try_from(b).unwrap());
+    }
+
+    #[test]
+    fn parse_borrowed_without_via() {
+        let b = &b"N0CALL>APRS::DEST     :Hello{42"[..];
+        let result = AprsPacketRef::try_from(b).unwrap();
+        assert_eq!(result.via().count(), 0);
+
+        match &result.data {
+            AprsDataRef::Message(msg) => {
+                assert_eq!(msg.addressee, b"DEST");
+                assert_eq!(msg.text, b"Hello");
+                assert_eq!(msg.id, Some(&b"42"[..]));
+            }
+            _ => panic!("Unexpected data type"),
+        }
+
+        assert_eq!(result.into_owned(), AprsPacket::try_from(b).unwrap());
+    }
+
+    #[test]
+    fn parse_borrowed_invalid_via() {
+        assert_eq!(
+            AprsPacketRef::try_from(&b"N0CALL>APRS,WIDE1-:>status"[..]),
+            Err(AprsError::EmptySSID("WIDE1-".to_owned()))
+        );
+    }
+
     #[test]
     fn e2e_serialize_deserialize() {
         let valids = vec![
@@ -166,6 +602,19 @@ mod tests {
             r"ICA3D17F2>APRS,qAS,dl4mea:=4821.61N\01224.49E^322/103/A=003054 !W09! id213D17F2 -039fpm +0.0rot 2.5dB 3e -0.0kHz gps1x1",
             r"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :Hello World! This msg has a : colon {32975",
             r"ICA3D17F2>Aprs,qAS,dl4mea::DESTINATI:Hello World! This msg has a : colon ",
+            r"N0CALL>APRS,WIDE1-1:{Q1user defined",
+            r"N0CALL>APRS,WIDE1-1:{{Xexperimental",
+            r"N0CALL>APRS,WIDE1-1:,test data",
+            r"N0CALL>APRS,WIDE1-1:>status text",
+            r"N0CALL>APRS,WIDE1-1:;OBJECT   *111111z4903.50N/07201.75WA",
+            r"N0CALL>APRS,WIDE1-1:`(_fn Oj/]Mic-E",
+            r"N0CALL>APRS,WIDE1-1:",
+            r"N0CALL>APRS:/992356/4903.50N/07201.75W-",
+            r"N0CALL>APRS:/123456Z4903.50N/07201.75W-",
+            r"N0CALL>APRS:/123456H4903.50N/07201.75W-",
+            r"N0CALL>APRS:!/ABCD#$%^- >C",
+            r#"N0CALL>APRS:!/!!!"!!!"- sT"#,
+            r"N0CALL>APRS:!/!!!!!!!!-  C",
         ];
 
         for v in valids {
@@ -178,6 +627,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn encode_to_slice() {
+        let original =
+            &br"ICA3D17F2>APRS,qAS,dl4mea:/074849h4821.61N\01224.49E^322/103/A=003054"[..];
+        let packet = AprsPacket::try_from(original).unwrap();
+        assert_eq!(packet.encoded_len().unwrap(), original.len());
+
+        let mut buf = [0; 256];
+        let len = packet.encode_to_slice(&mut buf).unwrap();
+        assert_eq!(&buf[..len], original);
+
+        let mut buf = [0; 16];
+        assert!(matches!(
+            packet.encode_to_slice(&mut buf),
+            Err(EncodeError::BufferTooSmall(len)) if len == original.len()
+        ));
+    }
+
     #[test]
     fn e2e_invalid_string_msg() {
         let original = b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :Hello World! This msg has raw bytes that are invalid utf8! \xc3\x28 {32975";
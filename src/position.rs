@@ -1,32 +1,54 @@
-use std::convert::TryFrom;
-use std::io::Write;
-
-use lonlat::{Latitude, Longitude};
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use bytes::parse_bytes;
+use geo::EARTH_RADIUS_KM;
+use lonlat::{Dao, Latitude, Longitude};
+use math;
+use Angle;
 use AprsCompressedCs;
 use AprsCompressionType;
 use AprsError;
 use EncodeError;
+use EncodeWrite;
+use Length;
+use Precision;
+use ParseField;
+use ParseWarning;
+use Speed;
+use Symbol;
 use Timestamp;
+use TurnRate;
+use VerticalSpeed;
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AprsPosition {
+    /// Not range-checked while parsing, so e.g. `993499z` is accepted. Use
+    /// [`AprsPacket::parse_strict`](crate::AprsPacket::parse_strict) or
+    /// [`Timestamp::validate`] to reject such timestamps.
     pub timestamp: Option<Timestamp>,
     pub messaging_supported: bool,
     pub latitude: Latitude,
     pub longitude: Longitude,
     pub symbol_table: char,
     pub symbol_code: char,
+    #[cfg_attr(feature = "serde", serde(with = "::serde_support::bytes"))]
     pub comment: Vec<u8>,
     pub cst: AprsCst,
 }
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AprsCst {
     CompressedSome {
         cs: AprsCompressedCs,
         t: AprsCompressionType,
     },
-    CompressedNone,
+    /// The compressed course/speed and type bytes are ignored (`c` is a
+    /// space) or invalid, kept verbatim for re-encoding
+    CompressedNone([u8; 3]),
     Uncompressed,
 }
 
@@ -34,42 +56,107 @@ impl TryFrom<&[u8]> for AprsPosition {
     type Error = AprsError;
 
     fn try_from(b: &[u8]) -> Result<Self, Self::Error> {
+        AprsPositionRef::try_from(b).map(AprsPositionRef::into_owned)
+    }
+}
+
+/// Borrowed version of [`AprsPosition`]
+#[derive(PartialEq, Debug, Clone)]
+pub struct AprsPositionRef<'a> {
+    pub timestamp: Option<Timestamp>,
+    pub messaging_supported: bool,
+    pub latitude: Latitude,
+    pub longitude: Longitude,
+    pub symbol_table: char,
+    pub symbol_code: char,
+    pub comment: &'a [u8],
+    pub cst: AprsCst,
+}
+
+impl<'a> TryFrom<&'a [u8]> for AprsPositionRef<'a> {
+    type Error = AprsError;
+
+    fn try_from(b: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(b, 0, None).map_err(|w| w.reason)
+    }
+}
+
+impl<'a> AprsPositionRef<'a> {
+    pub fn into_owned(self) -> AprsPosition {
+        AprsPosition {
+            timestamp: self.timestamp,
+            messaging_supported: self.messaging_supported,
+            latitude: self.latitude,
+            longitude: self.longitude,
+            symbol_table: self.symbol_table,
+            symbol_code: self.symbol_code,
+            comment: self.comment.to_vec(),
+            cst: self.cst,
+        }
+    }
+
+    /// `offset` is the position of `b` in the packet. With `warnings`, an
+    /// invalid timestamp is kept as [`Timestamp::Unsupported`] and an
+    /// invalid compressed `cs` is dropped.
+    pub(crate) fn parse(
+        b: &'a [u8],
+        offset: usize,
+        mut warnings: Option<&mut Vec<ParseWarning>>,
+    ) -> Result<Self, ParseWarning> {
+        let invalid = |reason| ParseWarning::new(ParseField::Position, offset, b.len(), reason);
+
         let first = *b
             .first()
-            .ok_or_else(|| AprsError::InvalidPosition(vec![]))?;
+            .ok_or_else(|| invalid(AprsError::InvalidPosition(vec![])))?;
         let messaging_supported = first == b'=' || first == b'@';
 
         // parse timestamp if necessary
         let has_timestamp = first == b'@' || first == b'/';
         let timestamp = if has_timestamp {
-            Some(Timestamp::try_from(
-                b.get(1..8)
-                    .ok_or_else(|| AprsError::InvalidPosition(b.to_vec()))?,
-            )?)
+            let raw = b
+                .get(1..8)
+                .ok_or_else(|| invalid(AprsError::InvalidPosition(b.to_vec())))?;
+            match Timestamp::try_from(raw) {
+                Ok(timestamp) => Some(timestamp),
+                Err(e) => {
+                    ParseWarning::new(ParseField::Timestamp, offset + 1, 7, e)
+                        .recover(warnings.as_deref_mut())?;
+                    Some(Timestamp::Unsupported(raw.to_vec()))
+                }
+            }
         } else {
             None
         };
 
         // strip leading type symbol and potential timestamp
-        let b = if has_timestamp { &b[8..] } else { &b[1..] };
+        let (b, offset) = if has_timestamp {
+            (&b[8..], offset + 8)
+        } else {
+            (&b[1..], offset + 1)
+        };
 
         // check for compressed position format
         let is_uncompressed_position = (*b.first().unwrap_or(&0) as char).is_numeric();
         match is_uncompressed_position {
-            true => Self::parse_uncompressed(b, timestamp, messaging_supported),
-            false => Self::parse_compressed(b, timestamp, messaging_supported),
+            true => Self::parse_uncompressed(b, offset, timestamp, messaging_supported),
+            false => Self::parse_compressed(b, offset, warnings, timestamp, messaging_supported),
         }
     }
-}
 
-impl AprsPosition {
     fn parse_compressed(
-        b: &[u8],
+        b: &'a [u8],
+        offset: usize,
+        warnings: Option<&mut Vec<ParseWarning>>,
         timestamp: Option<Timestamp>,
         messaging_supported: bool,
-    ) -> Result<Self, AprsError> {
+    ) -> Result<Self, ParseWarning> {
         if b.len() < 13 {
-            return Err(AprsError::InvalidPosition(b.to_owned()));
+            return Err(ParseWarning::new(
+                ParseField::Position,
+                offset,
+                b.len(),
+                AprsError::InvalidPosition(b.to_owned()),
+            ));
         }
 
         let symbol_table = b[0] as char;
@@ -79,24 +166,41 @@ impl AprsPosition {
         let course_speed = &b[10..12];
         let comp_type = b[12];
 
-        let latitude = Latitude::parse_compressed(comp_lat)?;
-        let longitude = Longitude::parse_compressed(comp_lon)?;
+        let latitude = Latitude::parse_compressed(comp_lat)
+            .map_err(|e| ParseWarning::new(ParseField::Latitude, offset + 1, 4, e))?;
+        let longitude = Longitude::parse_compressed(comp_lon)
+            .map_err(|e| ParseWarning::new(ParseField::Longitude, offset + 5, 4, e))?;
+
+        let raw_cst = [course_speed[0], course_speed[1], comp_type];
 
         // From the APRS spec - if the c value is a space,
         // the csT doesn't matter
         let cst = match course_speed[0] {
-            b' ' => AprsCst::CompressedNone,
+            b' ' => AprsCst::CompressedNone(raw_cst),
             _ => {
-                let t = comp_type
+                let cst = comp_type
                     .checked_sub(33)
-                    .ok_or_else(|| AprsError::InvalidPosition(b.to_owned()))?
-                    .into();
-                let cs = AprsCompressedCs::parse(course_speed[0], course_speed[1], t)?;
-                AprsCst::CompressedSome { cs, t }
+                    // only the lower 6 bits of T are defined
+                    .filter(|t| *t < 64)
+                    .ok_or_else(|| AprsError::InvalidPosition(b.to_owned()))
+                    .and_then(|t| {
+                        let t = t.into();
+                        let cs = AprsCompressedCs::parse(course_speed[0], course_speed[1], t)?;
+                        Ok(AprsCst::CompressedSome { cs, t })
+                    });
+
+                match cst {
+                    Ok(cst) => cst,
+                    Err(e) => {
+                        ParseWarning::new(ParseField::CompressedCs, offset + 10, 3, e)
+                            .recover(warnings)?;
+                        AprsCst::CompressedNone(raw_cst)
+                    }
+                }
             }
         };
 
-        let comment = b[13..].to_owned();
+        let comment = &b[13..];
 
         Ok(Self {
             timestamp,
@@ -111,22 +215,35 @@ impl AprsPosition {
     }
 
     fn parse_uncompressed(
-        b: &[u8],
+        b: &'a [u8],
+        offset: usize,
         timestamp: Option<Timestamp>,
         messaging_supported: bool,
-    ) -> Result<Self, AprsError> {
+    ) -> Result<Self, ParseWarning> {
         if b.len() < 19 {
-            return Err(AprsError::InvalidPosition(b.to_owned()));
+            return Err(ParseWarning::new(
+                ParseField::Position,
+                offset,
+                b.len(),
+                AprsError::InvalidPosition(b.to_owned()),
+            ));
         }
 
         // parse position
-        let latitude = Latitude::parse_uncompressed(&b[0..8])?;
-        let longitude = Longitude::parse_uncompressed(&b[9..18])?;
+        let mut latitude = Latitude::parse_uncompressed(&b[0..8])
+            .map_err(|e| ParseWarning::new(ParseField::Latitude, offset, 8, e))?;
+        let mut longitude = Longitude::parse_uncompressed(&b[9..18], latitude.ambiguity())
+            .map_err(|e| ParseWarning::new(ParseField::Longitude, offset + 9, 9, e))?;
 
         let symbol_table = b[8] as char;
         let symbol_code = b[18] as char;
 
-        let comment = b[19..].to_owned();
+        // the DAO extension stays in the comment, so it is encoded again
+        let comment = &b[19..];
+        if let Some(dao) = Dao::find(comment) {
+            latitude = latitude.refine(&dao);
+            longitude = longitude.refine(&dao);
+        }
 
         Ok(Self {
             timestamp,
@@ -139,8 +256,82 @@ impl AprsPosition {
             cst: AprsCst::Uncompressed,
         })
     }
+}
+
+impl AprsPosition {
+    /// `None` if the symbol table or code is invalid
+    pub fn symbol(&self) -> Option<Symbol> {
+        Symbol::new(self.symbol_table, self.symbol_code)
+    }
+
+    /// Size of the area the position stands for, the coarser of latitude
+    /// and longitude. `None` for positions created from exact values.
+    pub fn resolution(&self) -> Option<Length> {
+        let lat = self.latitude.resolution()?.radians();
+        let lon = self.longitude.resolution()?.radians()
+            * math::cos(Angle::from_degrees(*self.latitude).radians());
+
+        Some(Length::from_kilometers(lat.max(lon) * EARTH_RADIUS_KM))
+    }
+
+    /// Course and speed from the compressed `cs` bytes or the `ccc/sss`
+    /// extension at the start of the comment. `None` if the course is
+    /// unknown.
+    pub fn course_speed(&self) -> Option<(Angle, Speed)> {
+        match (&self.cst, self.comment.get(..7)) {
+            (
+                AprsCst::CompressedSome {
+                    cs: AprsCompressedCs::CourseSpeed(cs),
+                    ..
+                },
+                _,
+            ) => Some((cs.course(), cs.speed())),
+            (AprsCst::Uncompressed, Some(ext)) if ext[3] == b'/' => {
+                let course: u16 = parse_digits(&ext[..3])?;
+                let speed: u16 = parse_digits(&ext[4..])?;
+                // 000 is an unknown course
+                if course == 0 || course > 360 {
+                    return None;
+                }
+                Some((
+                    Angle::from_degrees(course.into()),
+                    Speed::from_knots(speed.into()),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Altitude from the compressed `cs` bytes or `/A=` in the comment
+    pub fn altitude(&self) -> Option<Length> {
+        if let AprsCst::CompressedSome {
+            cs: AprsCompressedCs::Altitude(altitude),
+            ..
+        } = &self.cst
+        {
+            return Some(altitude.altitude());
+        }
+
+        let start = self.comment.windows(3).position(|w| w == b"/A=")? + 3;
+        let feet: i32 = parse_bytes(self.comment.get(start..start + 6)?)?;
+        Some(Length::from_feet(feet.into()))
+    }
 
-    pub fn encode<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
+    /// Climb rate from an OGN `fpm` field in the comment
+    pub fn climb_rate(&self) -> Option<VerticalSpeed> {
+        comment_field(&self.comment, "fpm").map(VerticalSpeed::from_feet_per_minute)
+    }
+
+    /// Rate of turn from an OGN `rot` field in the comment
+    pub fn turn_rate(&self) -> Option<TurnRate> {
+        comment_field(&self.comment, "rot").map(TurnRate::from_half_turns_per_minute)
+    }
+
+    /// Uses the format given by `cst`. Fails with
+    /// [`EncodeError::UnsupportedPrecision`] if it can't carry the precision
+    /// of the coordinates: compressed coordinates can't be encoded
+    /// uncompressed, ambiguous or DAO coordinates can't be compressed.
+    pub fn encode<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
         let sym = match (self.timestamp.is_some(), self.messaging_supported) {
             (true, true) => '@',
             (true, false) => '/',
@@ -156,12 +347,20 @@ impl AprsPosition {
 
         match self.cst {
             AprsCst::Uncompressed => self.encode_uncompressed(buf),
-            AprsCst::CompressedSome { cs, t } => self.encode_compressed(buf, Some((cs, t))),
-            AprsCst::CompressedNone => self.encode_compressed(buf, None),
+            AprsCst::CompressedSome { cs, t } => self.encode_compressed_cst(buf, Ok((cs, t))),
+            AprsCst::CompressedNone(raw) => self.encode_compressed_cst(buf, Err(raw)),
         }
     }
 
-    pub fn encode_uncompressed<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
+    pub fn encode_uncompressed<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
+        // the extra digits are only kept in the comment
+        let dao = Dao::find(&self.comment).map(|dao| dao.precision());
+        for precision in [self.latitude.precision(), self.longitude.precision()].iter() {
+            if matches!(precision, Precision::Dao { .. }) && dao != Some(*precision) {
+                return Err(EncodeError::UnsupportedPrecision(*precision));
+            }
+        }
+
         self.latitude.encode_uncompressed(buf)?;
         write!(buf, "{}", self.symbol_table)?;
         self.longitude.encode_uncompressed(buf)?;
@@ -172,10 +371,19 @@ impl AprsPosition {
         Ok(())
     }
 
-    pub fn encode_compressed<W: Write>(
+    pub fn encode_compressed<W: EncodeWrite>(
         &self,
         buf: &mut W,
         extra: Option<(AprsCompressedCs, AprsCompressionType)>,
+    ) -> Result<(), EncodeError> {
+        self.encode_compressed_cst(buf, extra.ok_or(*b" sT"))
+    }
+
+    /// Encodes the csT bytes verbatim when `cst` is `Err`
+    fn encode_compressed_cst<W: EncodeWrite>(
+        &self,
+        buf: &mut W,
+        cst: Result<(AprsCompressedCs, AprsCompressionType), [u8; 3]>,
     ) -> Result<(), EncodeError> {
         write!(buf, "{}", self.symbol_table)?;
 
@@ -184,11 +392,11 @@ impl AprsPosition {
 
         write!(buf, "{}", self.symbol_code)?;
 
-        match extra {
-            Some((cs, t)) => {
+        match cst {
+            Ok((cs, t)) => {
                 cs.encode(buf, t)?;
             }
-            None => write!(buf, " sT")?,
+            Err(raw) => buf.write_all(&raw)?,
         };
 
         buf.write_all(&self.comment)?;
@@ -197,6 +405,21 @@ impl AprsPosition {
     }
 }
 
+fn parse_digits<T: core::str::FromStr>(b: &[u8]) -> Option<T> {
+    if !b.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    parse_bytes(b)
+}
+
+// a signed number followed by `unit`, separated by spaces
+fn comment_field(comment: &[u8], unit: &str) -> Option<f64> {
+    comment
+        .split(|b| *b == b' ')
+        .filter_map(|word| word.strip_suffix(unit.as_bytes()))
+        .find_map(parse_bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,6 +427,7 @@ mod tests {
     use AprsAltitude;
     use AprsCourseSpeed;
     use AprsRadioRange;
+    use Precision;
 
     #[test]
     fn parse_compressed_without_timestamp_or_messaging() {
@@ -215,8 +439,9 @@ mod tests {
         assert_relative_eq!(*result.longitude, -171.95429033460567);
         assert_eq!(result.symbol_table, '/');
         assert_eq!(result.symbol_code, '-');
+        assert_eq!(result.symbol(), Some(Symbol::HOUSE));
         assert_eq!(result.comment, []);
-        assert_eq!(result.cst, AprsCst::CompressedNone);
+        assert_eq!(result.cst, AprsCst::CompressedNone(*b" >C"));
     }
 
     #[test]
@@ -303,7 +528,7 @@ mod tests {
         assert_eq!(result.symbol_table, '\\');
         assert_eq!(result.symbol_code, '^');
         assert_eq!(result.comment, b"322/103/A=003054");
-        assert_eq!(result.cst, AprsCst::CompressedNone);
+        assert_eq!(result.cst, AprsCst::CompressedNone(*b" >C"));
     }
 
     #[test]
@@ -385,6 +610,8 @@ mod tests {
             &br"/074849h4821.61N\01224.49E^322/103/A=003054"[..],
             &b"=4903.50N/07201.75W-"[..],
             &br"@074849h4821.61N\01224.49E^322/103/A=003054"[..],
+            &b"!4903.50N/07201.75W-!W12! DAO"[..],
+            &b"!4903.50N/07201.75W-!w{!!"[..],
         ];
 
         for p in positions {
@@ -401,4 +628,102 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn encode_keeps_precision() {
+        let encode = |pos: &AprsPosition| {
+            let mut buf = vec![];
+            pos.encode(&mut buf).map(|_| buf)
+        };
+
+        // compressed coordinates would be rounded to 1/100 minute
+        let compressed = AprsPosition::try_from(&b"!/ABCD#$%^- sT"[..]).unwrap();
+        let pos = AprsPosition {
+            cst: AprsCst::Uncompressed,
+            ..compressed.clone()
+        };
+        assert!(matches!(
+            encode(&pos),
+            Err(EncodeError::UnsupportedPrecision(Precision::Compressed))
+        ));
+
+        // ambiguity can't be compressed
+        let ambiguous = AprsPosition::try_from(&b"!4903.  N/07201.  W-"[..]).unwrap();
+        let pos = AprsPosition {
+            cst: compressed.cst.clone(),
+            ..ambiguous
+        };
+        assert!(matches!(
+            encode(&pos),
+            Err(EncodeError::UnsupportedPrecision(Precision::Uncompressed {
+                ambiguity: 2
+            }))
+        ));
+
+        // the DAO extension has to stay in the comment
+        let mut pos = AprsPosition::try_from(&b"!4903.50N/07201.75W-!W12!"[..]).unwrap();
+        assert_eq!(pos.latitude.precision(), Precision::Dao { base91: false });
+        pos.comment = b"!w12!".to_vec();
+        assert!(matches!(
+            encode(&pos),
+            Err(EncodeError::UnsupportedPrecision(Precision::Dao { base91: false }))
+        ));
+        pos.comment = b"moved !W12!".to_vec();
+        assert_eq!(encode(&pos).unwrap(), b"!4903.50N/07201.75W-moved !W12!");
+
+        // exact values fit any format
+        let exact = AprsPosition {
+            latitude: Latitude::new(*compressed.latitude).unwrap(),
+            longitude: Longitude::new(*compressed.longitude).unwrap(),
+            cst: AprsCst::Uncompressed,
+            ..compressed
+        };
+        assert!(encode(&exact).is_ok());
+    }
+
+    #[test]
+    fn resolution() {
+        let pos = AprsPosition::try_from(&b"!4903.50N/07201.75W-"[..]).unwrap();
+        assert_relative_eq!(pos.resolution().unwrap().meters(), 18.53, epsilon = 0.01);
+
+        let pos = AprsPosition::try_from(&b"!4903.  N/07201.  W-"[..]).unwrap();
+        assert_eq!(
+            pos.longitude.precision(),
+            Precision::Uncompressed { ambiguity: 2 }
+        );
+        assert_relative_eq!(pos.resolution().unwrap().meters(), 1853.25, epsilon = 0.01);
+
+        let pos = AprsPosition::try_from(&b"!/5L!!<*e7> sT"[..]).unwrap();
+        assert!(pos.resolution().unwrap().meters() < 0.5);
+    }
+
+    #[test]
+    fn quantities() {
+        let pos = AprsPosition::try_from(
+            &br"/074849h4821.61N\01224.49E^322/103/A=003054 !W09! id213D17F2 -039fpm +0.5rot"[..],
+        )
+        .unwrap();
+        let (course, speed) = pos.course_speed().unwrap();
+        assert_relative_eq!(course.degrees(), 322.0);
+        assert_relative_eq!(speed.knots(), 103.0);
+        assert_relative_eq!(pos.altitude().unwrap().feet(), 3054.0);
+        assert_relative_eq!(pos.climb_rate().unwrap().feet_per_minute(), -39.0);
+        assert_relative_eq!(pos.turn_rate().unwrap().degrees_per_second(), 1.5);
+
+        let pos = AprsPosition::try_from(&b"!4903.50N/07201.75W-000/000/A=-00012"[..]).unwrap();
+        assert_eq!(pos.course_speed(), None);
+        assert_relative_eq!(pos.altitude().unwrap().feet(), -12.0);
+        assert_eq!(pos.climb_rate(), None);
+        assert_eq!(pos.turn_rate(), None);
+
+        let pos = AprsPosition::try_from(&b"!/ABCD#$%^-X>DHello"[..]).unwrap();
+        let (course, speed) = pos.course_speed().unwrap();
+        assert_relative_eq!(course.degrees(), 220.0);
+        assert_relative_eq!(speed.knots(), 8.317274897290226);
+        assert_eq!(pos.altitude(), None);
+
+        let pos = AprsPosition::try_from(&b"=/ABCD#$%^-S]1/A=001000"[..]).unwrap();
+        assert_eq!(pos.course_speed(), None);
+        assert_relative_eq!(pos.altitude().unwrap().feet(), 10004.520050700292);
+    }
 }
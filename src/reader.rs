@@ -0,0 +1,207 @@
+// line based reading of APRS-IS and TNC2 feeds
+
+use std::convert::TryFrom;
+use std::io::{BufRead, ErrorKind};
+
+use AprsError;
+use AprsPacket;
+
+/// Lines longer than this are rejected by default.
+/// APRS-IS limits lines to 512 bytes including CR/LF.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 512;
+
+// packets are by far the most common lines, boxing them is not worth it
+#[allow(clippy::large_enum_variant)]
+#[derive(PartialEq, Debug, Clone)]
+pub enum AprsLine {
+    Packet(AprsPacket),
+    /// Server comment without the leading `#`, e.g. a banner or keepalive
+    ServerComment(Vec<u8>),
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ReadLine {
+    /// Line without the trailing CR/LF.
+    /// Truncated to the maximum line length for over-long lines.
+    pub raw: Vec<u8>,
+    pub parsed: Result<AprsLine, AprsError>,
+}
+
+/// Iterator over the lines of an APRS-IS or TNC2 feed
+///
+/// Empty lines are skipped, `#` lines are returned as server comments and all
+/// other lines are parsed as packets.
+pub struct AprsReader<R> {
+    inner: R,
+    max_line_length: usize,
+}
+
+impl<R: BufRead> AprsReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+        }
+    }
+
+    /// Sets the maximum line length excluding the line terminator
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = max_line_length;
+        self
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads one line into `line` and returns its full length
+    /// or `None` at the end of the input.
+    fn read_line(&mut self, line: &mut Vec<u8>) -> std::io::Result<Option<usize>> {
+        let mut len = 0;
+        let mut last = None;
+        let mut eof = true;
+
+        loop {
+            let available = match self.inner.fill_buf() {
+                Ok(b) => b,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            if available.is_empty() {
+                break;
+            }
+            eof = false;
+
+            let (chunk, done) = match available.iter().position(|b| *b == b'\n') {
+                Some(i) => (&available[..i], true),
+                None => (available, false),
+            };
+
+            // never buffer more than one byte beyond the limit
+            let room = (self.max_line_length + 1).saturating_sub(line.len());
+            line.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            len += chunk.len();
+            if let Some(b) = chunk.last() {
+                last = Some(*b);
+            }
+
+            let consumed = chunk.len() + usize::from(done);
+            self.inner.consume(consumed);
+
+            if done {
+                break;
+            }
+        }
+
+        if eof {
+            return Ok(None);
+        }
+
+        if last == Some(b'\r') {
+            len -= 1;
+            line.truncate(len);
+        }
+
+        Ok(Some(len))
+    }
+}
+
+impl<R: BufRead> Iterator for AprsReader<R> {
+    type Item = std::io::Result<ReadLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut raw = vec![];
+            let len = match self.read_line(&mut raw) {
+                Ok(Some(len)) => len,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if len > self.max_line_length {
+                raw.truncate(self.max_line_length);
+                return Some(Ok(ReadLine {
+                    raw,
+                    parsed: Err(AprsError::LineTooLong(len)),
+                }));
+            }
+
+            let parsed = match raw.first() {
+                None => continue,
+                Some(b'#') => Ok(AprsLine::ServerComment(raw[1..].to_vec())),
+                Some(_) => AprsPacket::try_from(&raw[..]).map(AprsLine::Packet),
+            };
+
+            return Some(Ok(ReadLine { raw, parsed }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    fn read_all(input: &[u8], max_line_length: usize) -> Vec<ReadLine> {
+        // a tiny buffer makes sure lines span multiple reads
+        AprsReader::new(BufReader::with_capacity(4, input))
+            .max_line_length(max_line_length)
+            .collect::<std::io::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn packets_and_comments() {
+        let input = b"# aprsc 2.1.10\r\nICA3D17F2>Aprs,qAS,dl4mea::DEST     :Hello\r\n\r\nfoo\nICA3D17F2>Aprs,qAS,dl4mea::DEST     :Bye";
+        let lines = read_all(input, DEFAULT_MAX_LINE_LENGTH);
+
+        assert_eq!(lines.len(), 4);
+
+        assert_eq!(lines[0].raw, b"# aprsc 2.1.10");
+        assert_eq!(
+            lines[0].parsed,
+            Ok(AprsLine::ServerComment(b" aprsc 2.1.10".to_vec()))
+        );
+
+        assert_eq!(lines[1].raw, b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :Hello");
+        assert!(matches!(lines[1].parsed, Ok(AprsLine::Packet(_))));
+
+        assert_eq!(lines[2].raw, b"foo");
+        assert_eq!(
+            lines[2].parsed,
+            Err(AprsError::InvalidPacket(b"foo".to_vec()))
+        );
+
+        assert_eq!(lines[3].raw, b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :Bye");
+        assert!(matches!(lines[3].parsed, Ok(AprsLine::Packet(_))));
+    }
+
+    #[test]
+    fn over_long_lines() {
+        let input = b"0123456789abcdef\r\n#short\n0123456789\r\n";
+        let lines = read_all(input, 10);
+
+        assert_eq!(lines.len(), 3);
+
+        assert_eq!(lines[0].raw, b"0123456789");
+        assert_eq!(lines[0].parsed, Err(AprsError::LineTooLong(16)));
+
+        assert_eq!(lines[1].raw, b"#short");
+
+        // the CR does not count towards the limit
+        assert_eq!(lines[2].raw, b"0123456789");
+        assert_eq!(
+            lines[2].parsed,
+            Err(AprsError::InvalidPacket(b"0123456789".to_vec()))
+        );
+    }
+}
@@ -0,0 +1,219 @@
+// serde support for the packet types
+//
+// Most types derive `Serialize` and `Deserialize`. The types and fields in
+// here need a representation that differs from the derived one:
+//
+// - `Callsign`: a string like `"N0CALL-7"`
+// - `Latitude` and `Longitude`: a number, validated when deserializing. The
+//   precision and original minutes are not kept, deserialized coordinates
+//   are exact, so they don't compare equal to the parsed ones, and re-encode
+//   in their canonical form.
+// - byte fields (comments, message texts, ...): lossy UTF-8 strings in human
+//   readable formats like JSON, raw bytes in binary formats like
+//   MessagePack. With the `serde-base64` feature human readable formats use
+//   standard base64 strings instead, which keeps non-UTF8 data intact.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use Callsign;
+use Latitude;
+use Longitude;
+
+impl Serialize for Callsign {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Callsign {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Callsign::try_from(s.as_bytes()).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Latitude {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(**self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Latitude {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = f64::deserialize(deserializer)?;
+        Latitude::new(value).ok_or_else(|| D::Error::custom("invalid latitude"))
+    }
+}
+
+impl Serialize for Longitude {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(**self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Longitude {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = f64::deserialize(deserializer)?;
+        Longitude::new(value).ok_or_else(|| D::Error::custom("invalid longitude"))
+    }
+}
+
+/// `#[serde(with = "::serde_support::bytes")]` for `Vec<u8>` fields
+pub(crate) mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(b: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            serializer.serialize_bytes(b)
+        } else if cfg!(feature = "serde-base64") {
+            serializer.serialize_str(&base64::encode(b))
+        } else {
+            serializer.serialize_str(&String::from_utf8_lossy(b))
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a string or bytes")
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            if cfg!(feature = "serde-base64") {
+                base64::decode(v).ok_or_else(|| E::custom("invalid base64"))
+            } else {
+                Ok(v.as_bytes().to_vec())
+            }
+        }
+
+        fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(b) = seq.next_element()? {
+                bytes.push(b);
+            }
+            Ok(bytes)
+        }
+    }
+}
+
+/// `#[serde(with = "::serde_support::option_bytes")]` for `Option<Vec<u8>>` fields
+pub(crate) mod option_bytes {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Borrowed<'a>(#[serde(with = "::serde_support::bytes")] &'a [u8]);
+
+    #[derive(Deserialize)]
+    struct Owned(#[serde(with = "::serde_support::bytes")] Vec<u8>);
+
+    pub fn serialize<S: Serializer>(b: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match b {
+            Some(b) => serializer.serialize_some(&Borrowed(b)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Ok(Option::<Owned>::deserialize(deserializer)?.map(|o| o.0))
+    }
+}
+
+#[cfg_attr(not(feature = "serde-base64"), allow(dead_code))]
+mod base64 {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn encode(b: &[u8]) -> String {
+        let mut s = String::with_capacity((b.len() + 2) / 3 * 4);
+
+        for chunk in b.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    s.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+                } else {
+                    s.push('=');
+                }
+            }
+        }
+
+        s
+    }
+
+    pub fn decode(s: &str) -> Option<Vec<u8>> {
+        let s = s.as_bytes();
+        if s.len() % 4 != 0 {
+            return None;
+        }
+
+        let mut b = Vec::with_capacity(s.len() / 4 * 3);
+        for chunk in s.chunks(4) {
+            let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+            if padding > 2 {
+                return None;
+            }
+
+            let mut n = 0u32;
+            for (i, c) in chunk[..4 - padding].iter().enumerate() {
+                let digit = ALPHABET.iter().position(|a| a == c)? as u32;
+                n |= digit << (18 - 6 * i);
+            }
+
+            b.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+        }
+
+        Some(b)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            for (raw, encoded) in &[
+                (&b""[..], ""),
+                (&b"f"[..], "Zg=="),
+                (&b"fo"[..], "Zm8="),
+                (&b"foo"[..], "Zm9v"),
+                (&b"foob"[..], "Zm9vYg=="),
+                (&b"\xc3\x28\xff"[..], "wyj/"),
+            ] {
+                assert_eq!(encode(raw), *encoded);
+                assert_eq!(decode(encoded).as_deref(), Some(*raw));
+            }
+
+            assert_eq!(decode("Zm9"), None);
+            assert_eq!(decode("Zm9*"), None);
+        }
+    }
+}
@@ -0,0 +1,610 @@
+// reliable messaging
+//
+// Messages with an ID are acknowledged by the addressee with a message
+// `ackID` (or rejected with `rejID`) and retransmitted with growing intervals
+// until that happens. The receiver acks every copy it gets but shows only the
+// first one. Reply-acks (`{MM}AA`) advertise that the sender understands
+// acks piggybacked on messages and carry the ack for message `AA`.
+//
+// The session doesn't do any I/O: the caller feeds it received packets,
+// sends what `poll_transmit` returns and handles the events.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use AprsData;
+use AprsMessage;
+use AprsPacket;
+use Callsign;
+use Clock;
+use EncodeError;
+use PacketBuilder;
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum MessageEvent {
+    /// A new message for us, retransmissions are only reported once
+    Received {
+        from: Callsign,
+        message: AprsMessage,
+    },
+    Delivered {
+        to: Callsign,
+        id: Vec<u8>,
+    },
+    Rejected {
+        to: Callsign,
+        id: Vec<u8>,
+    },
+    /// No ack after the last retransmission
+    TimedOut {
+        to: Callsign,
+        id: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Outgoing {
+    to: Callsign,
+    id: Vec<u8>,
+    packet: AprsPacket,
+    attempts: u32,
+    next_at: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct Seen {
+    from: String,
+    id: Vec<u8>,
+    at: Duration,
+}
+
+/// Sends and receives messages for one station, with acks, retransmission
+/// and duplicate suppression
+///
+/// ```rust
+/// # extern crate aprs_parser;
+/// # use std::convert::TryFrom;
+/// # use std::time::Duration;
+/// # use aprs_parser::{AprsPacket, Callsign, ManualClock, MessageEvent, MessageSession};
+/// let clock = ManualClock::new();
+/// let mut session = MessageSession::new(Callsign::new("N0CALL", None), &clock);
+///
+/// let id = session.send(&Callsign::new("DEST", None), "Hello").unwrap();
+/// let packet = session.poll_transmit().unwrap();
+/// let mut buf = vec![];
+/// packet.encode(&mut buf).unwrap();
+/// assert_eq!(buf, &b"N0CALL>APRS::DEST     :Hello{1"[..]);
+///
+/// // no ack yet, the message is sent again
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(session.poll_transmit(), Some(packet));
+///
+/// session.receive(&AprsPacket::try_from(&b"DEST>APRS::N0CALL   :ack1"[..]).unwrap());
+/// assert_eq!(
+///     session.poll_event(),
+///     Some(MessageEvent::Delivered { to: Callsign::new("DEST", None), id })
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessageSession<C> {
+    callsign: String,
+    builder: PacketBuilder,
+    clock: C,
+    retry_interval: Duration,
+    max_attempts: u32,
+    duplicate_window: Duration,
+    reply_ack: bool,
+    next_id: u32,
+    outgoing: Vec<Outgoing>,
+    seen: VecDeque<Seen>,
+    // last message ID received from each station, for reply-acks
+    unacked: BTreeMap<String, Vec<u8>>,
+    transmit: VecDeque<AprsPacket>,
+    events: VecDeque<MessageEvent>,
+}
+
+impl<C: Clock> MessageSession<C> {
+    /// Sends as `callsign` to the generic destination `APRS`. Messages are
+    /// sent up to 5 times, 30 s after the first attempt and twice as long
+    /// after each further one. Duplicates are suppressed for 10 minutes.
+    pub fn new(callsign: Callsign, clock: C) -> Self {
+        Self {
+            callsign: callsign.to_string(),
+            builder: PacketBuilder::from(callsign),
+            clock,
+            retry_interval: Duration::from_secs(30),
+            max_attempts: 5,
+            duplicate_window: Duration::from_secs(600),
+            reply_ack: false,
+            next_id: 1,
+            outgoing: Vec::new(),
+            seen: VecDeque::new(),
+            unacked: BTreeMap::new(),
+            transmit: VecDeque::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Destination of outgoing packets, usually the software identifier
+    pub fn to<T: AsRef<str>>(mut self, to: T) -> Self {
+        self.builder = self.builder.to(to);
+        self
+    }
+
+    /// Digipeater path of outgoing packets
+    pub fn path<I>(mut self, path: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.builder = self.builder.path(path);
+        self
+    }
+
+    /// Wait before the first retransmission, doubled for each further one
+    pub fn retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Number of transmissions, including the first one
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// How long a message ID from the same station counts as a duplicate
+    pub fn duplicate_window(mut self, duplicate_window: Duration) -> Self {
+        self.duplicate_window = duplicate_window;
+        self
+    }
+
+    /// Sends IDs as `MM}AA`, acknowledging the last message from the
+    /// addressee in every message
+    pub fn reply_ack(mut self, reply_ack: bool) -> Self {
+        self.reply_ack = reply_ack;
+        self
+    }
+
+    /// Queues a message and returns its ID, which is used in the events
+    /// about it. Fails if the addressee, path or text is invalid, text longer
+    /// than [`MAX_MESSAGE_TEXT_LENGTH`](crate::MAX_MESSAGE_TEXT_LENGTH) has
+    /// to be sent with [`MessageSession::send_long`].
+    pub fn send<T: AsRef<[u8]>>(&mut self, to: &Callsign, text: T) -> Result<Vec<u8>, EncodeError> {
+        let addressee = to.to_string();
+        if addressee.len() > 9 {
+            return Err(EncodeError::InvalidMessageAddressee(addressee.into_bytes()));
+        }
+        AprsMessage::validate_text(text.as_ref())?;
+
+        let id = self.next_id.to_string().into_bytes();
+        let mut wire_id = id.clone();
+        if self.reply_ack {
+            wire_id.push(b'}');
+            if let Some(ack) = self.unacked.remove(&addressee) {
+                wire_id.extend_from_slice(&ack);
+            }
+        }
+
+        let packet = self
+            .builder
+            .clone()
+            .data(AprsMessage {
+                addressee: addressee.clone().into_bytes(),
+                text: text.as_ref().to_vec(),
+                id: Some(wire_id),
+            })
+            .build()?;
+
+        self.next_id = self.next_id % 99_999 + 1;
+        self.outgoing.push(Outgoing {
+            to: to.clone(),
+            id: id.clone(),
+            packet,
+            attempts: 0,
+            next_at: self.clock.now(),
+        });
+
+        Ok(id)
+    }
+
+    /// Queues text of any length, split as by [`AprsMessage::split`], and
+    /// returns the IDs of the parts
+    pub fn send_long(&mut self, to: &Callsign, text: &str) -> Result<Vec<Vec<u8>>, EncodeError> {
+        // check everything before queueing the first part
+        let parts = AprsMessage::split(to.to_string(), text, self.next_id)?;
+        self.builder.clone().data(parts[0].clone()).build()?;
+
+        parts
+            .into_iter()
+            .map(|part| self.send(to, part.text))
+            .collect()
+    }
+
+    /// Handles a received packet. Packets that aren't messages for us are
+    /// ignored.
+    pub fn receive(&mut self, packet: &AprsPacket) {
+        let message = match &packet.data {
+            AprsData::Message(message) if self.is_for_us(&message.addressee) => message,
+            _ => return,
+        };
+        let from = packet.from.to_string();
+
+        let id = match &message.id {
+            Some(id) => id,
+            None => {
+                match (message.text.get(..3), message.text.get(3..)) {
+                    (Some(b"ack"), Some(id)) => self.acknowledged(&from, id, false),
+                    (Some(b"rej"), Some(id)) => self.acknowledged(&from, id, true),
+                    _ => self.events.push_back(MessageEvent::Received {
+                        from: packet.from.clone(),
+                        message: message.clone(),
+                    }),
+                }
+                return;
+            }
+        };
+
+        // `MM}AA` carries the ack for our message `AA`
+        let (id, reply_ack) = match id.iter().position(|b| *b == b'}') {
+            Some(i) => (&id[..i], Some(&id[i + 1..])),
+            None => (&id[..], None),
+        };
+        if let Some(ack) = reply_ack.filter(|ack| !ack.is_empty()) {
+            self.acknowledged(&from, ack, false);
+        }
+
+        let mut ack = b"ack".to_vec();
+        ack.extend_from_slice(id);
+        if let Ok(packet) = self
+            .builder
+            .clone()
+            .data(AprsMessage {
+                addressee: from.clone().into_bytes(),
+                text: ack,
+                id: None,
+            })
+            .build()
+        {
+            self.transmit.push_back(packet);
+        }
+
+        let now = self.clock.now();
+        self.forget_seen(now);
+        if self
+            .seen
+            .iter()
+            .any(|seen| seen.from == from && seen.id == id)
+        {
+            return;
+        }
+
+        self.seen.push_back(Seen {
+            from: from.clone(),
+            id: id.to_vec(),
+            at: now,
+        });
+        if reply_ack.is_some() {
+            self.unacked.insert(from, id.to_vec());
+        }
+        self.events.push_back(MessageEvent::Received {
+            from: packet.from.clone(),
+            message: AprsMessage {
+                id: Some(id.to_vec()),
+                ..message.clone()
+            },
+        });
+    }
+
+    /// The next packet to send now: acks first, then due (re)transmissions
+    pub fn poll_transmit(&mut self) -> Option<AprsPacket> {
+        if let Some(packet) = self.transmit.pop_front() {
+            return Some(packet);
+        }
+
+        let now = self.clock.now();
+        while let Some(i) = self.outgoing.iter().position(|o| o.next_at <= now) {
+            let outgoing = &mut self.outgoing[i];
+            if outgoing.attempts >= self.max_attempts {
+                let outgoing = self.outgoing.remove(i);
+                self.events.push_back(MessageEvent::TimedOut {
+                    to: outgoing.to,
+                    id: outgoing.id,
+                });
+                continue;
+            }
+
+            outgoing.next_at = now + self.retry_interval * (1 << outgoing.attempts.min(16));
+            outgoing.attempts += 1;
+            return Some(outgoing.packet.clone());
+        }
+
+        None
+    }
+
+    pub fn poll_event(&mut self) -> Option<MessageEvent> {
+        self.events.pop_front()
+    }
+
+    /// When `poll_transmit` has something to do next, `None` if no message
+    /// is waiting for an ack
+    pub fn next_deadline(&self) -> Option<Duration> {
+        if !self.transmit.is_empty() {
+            return Some(self.clock.now());
+        }
+
+        self.outgoing.iter().map(|o| o.next_at).min()
+    }
+
+    /// Number of messages waiting for an ack
+    pub fn pending(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    fn is_for_us(&self, addressee: &[u8]) -> bool {
+        addressee.eq_ignore_ascii_case(self.callsign.as_bytes())
+    }
+
+    fn acknowledged(&mut self, from: &str, id: &[u8], rejected: bool) {
+        // reply-ack capable stations may ack with `MM}AA`
+        let id = id.split(|b| *b == b'}').next().unwrap_or(id);
+        let i = match self
+            .outgoing
+            .iter()
+            .position(|o| o.to.to_string().eq_ignore_ascii_case(from) && o.id == id)
+        {
+            Some(i) => i,
+            None => return,
+        };
+
+        let Outgoing { to, id, .. } = self.outgoing.remove(i);
+        self.events.push_back(match rejected {
+            false => MessageEvent::Delivered { to, id },
+            true => MessageEvent::Rejected { to, id },
+        });
+    }
+
+    fn forget_seen(&mut self, now: Duration) {
+        while let Some(seen) = self.seen.front() {
+            if now < seen.at + self.duplicate_window {
+                break;
+            }
+            self.seen.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+    use ManualClock;
+
+    fn packet(b: &str) -> AprsPacket {
+        AprsPacket::try_from(b.as_bytes()).unwrap()
+    }
+
+    fn encoded(packet: Option<AprsPacket>) -> Option<String> {
+        packet.map(|packet| {
+            let mut buf = vec![];
+            packet.encode(&mut buf).unwrap();
+            String::from_utf8(buf).unwrap()
+        })
+    }
+
+    fn dest() -> Callsign {
+        Callsign::new("DEST", None)
+    }
+
+    #[test]
+    fn retransmit_with_backoff() {
+        let clock = ManualClock::new();
+        let mut session = MessageSession::new(Callsign::new("N0CALL", Some("1")), &clock)
+            .to("APZ001")
+            .path(&["WIDE1-1"])
+            .retry_interval(Duration::from_secs(10))
+            .max_attempts(3);
+
+        let id = session.send(&dest(), "Hello").unwrap();
+        assert_eq!(id, b"1");
+        assert_eq!(session.next_deadline(), Some(Duration::from_secs(0)));
+
+        let expected = Some("N0CALL-1>APZ001,WIDE1-1::DEST     :Hello{1".to_string());
+        assert_eq!(encoded(session.poll_transmit()), expected);
+        assert_eq!(session.poll_transmit(), None);
+        assert_eq!(session.next_deadline(), Some(Duration::from_secs(10)));
+
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(session.poll_transmit(), None);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(encoded(session.poll_transmit()), expected);
+        assert_eq!(session.next_deadline(), Some(Duration::from_secs(30)));
+
+        clock.set(Duration::from_secs(30));
+        assert_eq!(encoded(session.poll_transmit()), expected);
+        assert_eq!(session.next_deadline(), Some(Duration::from_secs(70)));
+        assert_eq!(session.poll_event(), None);
+
+        clock.set(Duration::from_secs(70));
+        assert_eq!(session.poll_transmit(), None);
+        assert_eq!(
+            session.poll_event(),
+            Some(MessageEvent::TimedOut { to: dest(), id })
+        );
+        assert_eq!(session.pending(), 0);
+        assert_eq!(session.next_deadline(), None);
+    }
+
+    #[test]
+    fn ack_and_reject() {
+        let clock = ManualClock::new();
+        let mut session = MessageSession::new(Callsign::new("N0CALL", None), &clock);
+
+        let first = session.send(&dest(), "one").unwrap();
+        let second = session.send(&dest(), "two").unwrap();
+        assert_eq!(second, b"2");
+        assert!(session.poll_transmit().is_some());
+        assert!(session.poll_transmit().is_some());
+
+        // wrong sender or unknown ID
+        session.receive(&packet("OTHER>APRS::N0CALL   :ack1"));
+        session.receive(&packet("DEST>APRS::N0CALL   :ack3"));
+        assert_eq!(session.pending(), 2);
+
+        session.receive(&packet("DEST>APRS::n0call   :rej2"));
+        session.receive(&packet("DEST>APRS::N0CALL   :ack1"));
+        assert_eq!(
+            session.poll_event(),
+            Some(MessageEvent::Rejected {
+                to: dest(),
+                id: second
+            })
+        );
+        assert_eq!(
+            session.poll_event(),
+            Some(MessageEvent::Delivered {
+                to: dest(),
+                id: first
+            })
+        );
+        assert_eq!(session.poll_event(), None);
+
+        // late acks are ignored
+        session.receive(&packet("DEST>APRS::N0CALL   :ack1"));
+        assert_eq!(session.poll_event(), None);
+
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(session.poll_transmit(), None);
+    }
+
+    #[test]
+    fn receive_with_duplicates() {
+        let clock = ManualClock::new();
+        let mut session = MessageSession::new(Callsign::new("N0CALL", None), &clock)
+            .duplicate_window(Duration::from_secs(60));
+
+        let message = "DEST>APRS::N0CALL   :Hi there{42";
+        session.receive(&packet(message));
+        assert_eq!(
+            session.poll_event(),
+            Some(MessageEvent::Received {
+                from: dest(),
+                message: AprsMessage {
+                    addressee: b"N0CALL".to_vec(),
+                    text: b"Hi there".to_vec(),
+                    id: Some(b"42".to_vec()),
+                }
+            })
+        );
+        assert_eq!(
+            encoded(session.poll_transmit()),
+            Some("N0CALL>APRS::DEST     :ack42".to_string())
+        );
+
+        // retransmissions are acked again but not reported
+        clock.advance(Duration::from_secs(30));
+        session.receive(&packet(message));
+        assert_eq!(session.poll_event(), None);
+        assert_eq!(
+            encoded(session.poll_transmit()),
+            Some("N0CALL>APRS::DEST     :ack42".to_string())
+        );
+
+        clock.advance(Duration::from_secs(30));
+        session.receive(&packet(message));
+        assert!(session.poll_event().is_some());
+        assert!(session.poll_transmit().is_some());
+
+        // not for us
+        session.receive(&packet("DEST>APRS::OTHER    :Hi there{43"));
+        session.receive(&packet("DEST>APRS:>status"));
+        assert_eq!(session.poll_event(), None);
+
+        // without ID, nothing to ack
+        session.receive(&packet("DEST>APRS::N0CALL   :bulletin"));
+        assert!(session.poll_event().is_some());
+        assert_eq!(session.poll_transmit(), None);
+    }
+
+    #[test]
+    fn reply_ack() {
+        let clock = ManualClock::new();
+        let mut session =
+            MessageSession::new(Callsign::new("N0CALL", None), &clock).reply_ack(true);
+
+        session.send(&dest(), "Hello").unwrap();
+        assert_eq!(
+            encoded(session.poll_transmit()),
+            Some("N0CALL>APRS::DEST     :Hello{1}".to_string())
+        );
+
+        // the answer acks our message and advertises reply-acks
+        session.receive(&packet("DEST>APRS::N0CALL   :Hi{AB}1"));
+        assert_eq!(
+            session.poll_event(),
+            Some(MessageEvent::Delivered {
+                to: dest(),
+                id: b"1".to_vec()
+            })
+        );
+        assert!(matches!(
+            session.poll_event(),
+            Some(MessageEvent::Received { message, .. }) if message.id == Some(b"AB".to_vec())
+        ));
+        assert_eq!(
+            encoded(session.poll_transmit()),
+            Some("N0CALL>APRS::DEST     :ackAB".to_string())
+        );
+
+        session.send(&dest(), "Bye").unwrap();
+        assert_eq!(
+            encoded(session.poll_transmit()),
+            Some("N0CALL>APRS::DEST     :Bye{2}AB".to_string())
+        );
+        session.receive(&packet("DEST>APRS::N0CALL   :ack2}"));
+        assert!(matches!(
+            session.poll_event(),
+            Some(MessageEvent::Delivered { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_addressee() {
+        let mut session = MessageSession::new(Callsign::new("N0CALL", None), ManualClock::new());
+        assert!(session
+            .send(&Callsign::new("TOOLONGCALL", None), "Hello")
+            .is_err());
+        assert_eq!(session.pending(), 0);
+    }
+
+    #[test]
+    fn send_long() {
+        let mut session = MessageSession::new(Callsign::new("N0CALL", None), ManualClock::new());
+        session.send(&dest(), "first").unwrap();
+
+        let text = "a long message ".repeat(6);
+        let ids = session.send_long(&dest(), &text).unwrap();
+        assert_eq!(ids, vec![b"2".to_vec(), b"3".to_vec()]);
+        assert_eq!(session.pending(), 3);
+
+        assert!(session.poll_transmit().is_some());
+        assert_eq!(
+            encoded(session.poll_transmit()),
+            Some(
+                "N0CALL>APRS::DEST     :a long message a long message a long message a long message a (1/2){2"
+                    .to_string()
+            )
+        );
+        assert!(session.poll_transmit().is_some());
+
+        assert!(session.send_long(&dest(), "a|b").is_err());
+        assert!(session.send(&dest(), "a~b").is_err());
+        assert!(matches!(
+            session.send(&dest(), "x".repeat(200)),
+            Err(EncodeError::MessageTextTooLong(200))
+        ));
+        assert_eq!(session.pending(), 3);
+    }
+}
@@ -0,0 +1,544 @@
+// APRS symbols
+//
+// A symbol is a symbol table identifier and a symbol code. The table is either
+// the primary table `/`, the alternate table `\` or an overlay character
+// (`0-9`, `A-Z`) on top of an alternate table symbol. Compressed positions
+// write numeric overlays as `a-j`.
+//
+// Stations without position can also put their symbol into the destination
+// callsign, e.g. `GPSMV` for a car or `GPSODA` for a digipeater with overlay `A`.
+
+use alloc::string::String;
+
+use Callsign;
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Symbol {
+    table: char,
+    code: char,
+}
+
+impl Symbol {
+    pub const POLICE: Symbol = Symbol::primary('!');
+    pub const DIGI: Symbol = Symbol::primary('#');
+    pub const SMALL_AIRCRAFT: Symbol = Symbol::primary('\'');
+    pub const HOUSE: Symbol = Symbol::primary('-');
+    pub const RED_DOT: Symbol = Symbol::primary('/');
+    pub const MOTORCYCLE: Symbol = Symbol::primary('<');
+    pub const CAR: Symbol = Symbol::primary('>');
+    pub const BALLOON: Symbol = Symbol::primary('O');
+    pub const HELICOPTER: Symbol = Symbol::primary('X');
+    pub const YACHT: Symbol = Symbol::primary('Y');
+    pub const JOGGER: Symbol = Symbol::primary('[');
+    pub const LARGE_AIRCRAFT: Symbol = Symbol::primary('^');
+    pub const WEATHER_STATION: Symbol = Symbol::primary('_');
+    pub const BICYCLE: Symbol = Symbol::primary('b');
+    pub const GLIDER: Symbol = Symbol::primary('g');
+    pub const REPEATER: Symbol = Symbol::primary('r');
+    pub const SHIP: Symbol = Symbol::primary('s');
+    pub const TRUCK: Symbol = Symbol::primary('k');
+    pub const VAN: Symbol = Symbol::primary('v');
+    pub const EMERGENCY: Symbol = Symbol::alternate('!');
+    pub const GATEWAY: Symbol = Symbol::alternate('&');
+
+    /// Creates a symbol from a symbol table identifier and a symbol code.
+    /// Returns `None` if either is invalid.
+    pub fn new(table: char, code: char) -> Option<Self> {
+        let table = match table {
+            '/' | '\\' | '0'..='9' | 'A'..='Z' => table,
+            'a'..='j' => (table as u8 - b'a' + b'0') as char,
+            _ => return None,
+        };
+
+        if !('!'..='~').contains(&code) {
+            return None;
+        }
+
+        Some(Self { table, code })
+    }
+
+    const fn primary(code: char) -> Self {
+        Self { table: '/', code }
+    }
+
+    const fn alternate(code: char) -> Self {
+        Self { table: '\\', code }
+    }
+
+    /// Adds an overlay character (`0-9`, `A-Z`). Overlays always use the
+    /// alternate table.
+    pub fn with_overlay(self, overlay: char) -> Option<Self> {
+        match overlay {
+            '0'..='9' | 'A'..='Z' => Some(Self {
+                table: overlay,
+                code: self.code,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Symbol table identifier: `/`, `\` or the overlay character
+    pub fn table(&self) -> char {
+        self.table
+    }
+
+    pub fn code(&self) -> char {
+        self.code
+    }
+
+    pub fn overlay(&self) -> Option<char> {
+        match self.table {
+            '/' | '\\' => None,
+            overlay => Some(overlay),
+        }
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.table == '/'
+    }
+
+    /// Name of the symbol in the symbol table, e.g. "Car"
+    pub fn name(&self) -> &'static str {
+        let names = if self.is_primary() {
+            &PRIMARY_NAMES
+        } else {
+            &ALTERNATE_NAMES
+        };
+
+        names[(self.code as u8 - b'!') as usize]
+    }
+
+    /// Name including the overlay, e.g. "Overlayed digi (overlay S)"
+    pub fn description(&self) -> String {
+        match self.overlay() {
+            Some(overlay) => format!("{} (overlay {})", self.name(), overlay),
+            None => self.name().into(),
+        }
+    }
+
+    /// Destination callsign like `GPSMV` or `GPSODA`
+    pub fn to_destination(&self) -> Callsign {
+        let code = self.code as u8;
+        let group = DESTINATION_GROUPS
+            .iter()
+            .find(|g| (g.first..=g.last).contains(&code))
+            .expect("symbol codes are always within a group");
+
+        let prefix = if self.is_primary() {
+            group.primary
+        } else {
+            group.alternate
+        };
+        let suffix = group.first_suffix + (code - group.first);
+
+        let mut call = String::from("GPS");
+        call.push(prefix as char);
+        call.push(suffix as char);
+        if let Some(overlay) = self.overlay() {
+            call.push(overlay);
+        }
+
+        Callsign::new(call, None)
+    }
+
+    /// Parses the symbol from a `GPSxyz`, `SPCxyz` or `SYMxyz` destination
+    /// callsign or from the numbered `GPSCnn` and `GPSEnn` forms. The SSID is
+    /// ignored.
+    pub fn from_destination(destination: &Callsign) -> Option<Self> {
+        let call = destination.call.as_bytes();
+        if call.len() < 5 || call.len() > 6 {
+            return None;
+        }
+
+        let (prefix, rest) = call.split_at(3);
+        if prefix != b"GPS" && prefix != b"SPC" && prefix != b"SYM" {
+            return None;
+        }
+
+        if prefix == b"GPS" && (rest[0] == b'C' || rest[0] == b'E') {
+            if rest.len() != 3 || !rest[1].is_ascii_digit() || !rest[2].is_ascii_digit() {
+                return None;
+            }
+
+            let n = (rest[1] - b'0') * 10 + (rest[2] - b'0');
+            if !(1..=94).contains(&n) {
+                return None;
+            }
+
+            let table = if rest[0] == b'C' { '/' } else { '\\' };
+            return Self::new(table, (b'!' + n - 1) as char);
+        }
+
+        let (x, y) = (rest[0], rest[1]);
+        let (group, primary) = DESTINATION_GROUPS.iter().find_map(|g| {
+            let in_group = y >= g.first_suffix && y <= g.first_suffix + (g.last - g.first);
+            match x {
+                _ if !in_group => None,
+                x if x == g.primary => Some((g, true)),
+                x if x == g.alternate => Some((g, false)),
+                _ => None,
+            }
+        })?;
+
+        let code = (group.first + (y - group.first_suffix)) as char;
+        let symbol = if primary {
+            Self::primary(code)
+        } else {
+            Self::alternate(code)
+        };
+
+        match rest.get(2) {
+            Some(b' ') | None => Some(symbol),
+            Some(overlay) if !primary => symbol.with_overlay(*overlay as char),
+            Some(_) => None,
+        }
+    }
+}
+
+impl From<Symbol> for (char, char) {
+    fn from(symbol: Symbol) -> Self {
+        (symbol.table, symbol.code)
+    }
+}
+
+// range of symbol codes `first..=last` written as two characters in
+// destination callsigns, the prefix depends on the table
+struct DestinationGroup {
+    first: u8,
+    last: u8,
+    primary: u8,
+    alternate: u8,
+    first_suffix: u8,
+}
+
+const DESTINATION_GROUPS: [DestinationGroup; 7] = [
+    DestinationGroup {
+        first: b'!',
+        last: b'/',
+        primary: b'B',
+        alternate: b'O',
+        first_suffix: b'B',
+    },
+    DestinationGroup {
+        first: b'0',
+        last: b'9',
+        primary: b'P',
+        alternate: b'A',
+        first_suffix: b'0',
+    },
+    DestinationGroup {
+        first: b':',
+        last: b'@',
+        primary: b'M',
+        alternate: b'N',
+        first_suffix: b'R',
+    },
+    DestinationGroup {
+        first: b'A',
+        last: b'Z',
+        primary: b'P',
+        alternate: b'A',
+        first_suffix: b'A',
+    },
+    DestinationGroup {
+        first: b'[',
+        last: b'`',
+        primary: b'H',
+        alternate: b'D',
+        first_suffix: b'S',
+    },
+    DestinationGroup {
+        first: b'a',
+        last: b'z',
+        primary: b'L',
+        alternate: b'S',
+        first_suffix: b'A',
+    },
+    DestinationGroup {
+        first: b'{',
+        last: b'~',
+        primary: b'J',
+        alternate: b'Q',
+        first_suffix: b'1',
+    },
+];
+
+// symbol names from the APRS symbol tables, starting at `!`
+const PRIMARY_NAMES: [&str; 94] = [
+    "Police station",
+    "Reserved",
+    "Digipeater",
+    "Phone",
+    "DX cluster",
+    "HF gateway",
+    "Small aircraft",
+    "Mobile satellite station",
+    "Wheelchair",
+    "Snowmobile",
+    "Red Cross",
+    "Boy Scouts",
+    "House",
+    "X",
+    "Red dot",
+    "Circle (0)",
+    "Circle (1)",
+    "Circle (2)",
+    "Circle (3)",
+    "Circle (4)",
+    "Circle (5)",
+    "Circle (6)",
+    "Circle (7)",
+    "Circle (8)",
+    "Circle (9)",
+    "Fire",
+    "Campground",
+    "Motorcycle",
+    "Railroad engine",
+    "Car",
+    "File server",
+    "Hurricane future prediction",
+    "Aid station",
+    "BBS",
+    "Canoe",
+    "Unassigned",
+    "Eyeball",
+    "Farm vehicle",
+    "Grid square",
+    "Hotel",
+    "TCP/IP",
+    "Unassigned",
+    "School",
+    "PC user",
+    "MacAPRS",
+    "NTS station",
+    "Balloon",
+    "Police",
+    "TBD",
+    "Recreational vehicle",
+    "Space shuttle",
+    "SSTV",
+    "Bus",
+    "ATV",
+    "National Weather Service site",
+    "Helicopter",
+    "Yacht",
+    "WinAPRS",
+    "Jogger",
+    "DF station",
+    "Post office",
+    "Large aircraft",
+    "Weather station",
+    "Dish antenna",
+    "Ambulance",
+    "Bicycle",
+    "Incident command post",
+    "Fire department",
+    "Horse",
+    "Fire truck",
+    "Glider",
+    "Hospital",
+    "IOTA",
+    "Jeep",
+    "Truck",
+    "Laptop",
+    "Mic-E repeater",
+    "Node",
+    "Emergency operations center",
+    "Rover",
+    "Grid square above 128 m",
+    "Repeater",
+    "Ship",
+    "Truck stop",
+    "Semi truck",
+    "Van",
+    "Water station",
+    "xAPRS",
+    "Yagi at QTH",
+    "Unassigned",
+    "Unassigned",
+    "TNC stream switch",
+    "Unassigned",
+    "TNC stream switch",
+];
+
+const ALTERNATE_NAMES: [&str; 94] = [
+    "Emergency",
+    "Reserved",
+    "Overlayed digi",
+    "Bank or ATM",
+    "Power plant",
+    "Gateway",
+    "Crash site",
+    "Cloudy",
+    "Firenet MEO",
+    "Snow",
+    "Church",
+    "Girl Scouts",
+    "House (HF)",
+    "Ambiguous",
+    "Waypoint destination",
+    "Circle (IRLP, Echolink, WIRES)",
+    "Unassigned",
+    "Unassigned",
+    "Unassigned",
+    "Unassigned",
+    "Unassigned",
+    "Unassigned",
+    "Unassigned",
+    "Network node",
+    "Gas station",
+    "Hail",
+    "Park",
+    "Advisory",
+    "Unassigned",
+    "Overlayed car",
+    "Info kiosk",
+    "Hurricane",
+    "Overlay box",
+    "Blowing snow",
+    "Coast Guard",
+    "Depot",
+    "Smoke",
+    "Freezing rain",
+    "Snow shower",
+    "Haze",
+    "Rain shower",
+    "Lightning",
+    "Kenwood HT",
+    "Lighthouse",
+    "MARS",
+    "Navigation buoy",
+    "Rocket",
+    "Parking",
+    "Earthquake",
+    "Restaurant",
+    "Satellite",
+    "Thunderstorm",
+    "Sunny",
+    "VORTAC",
+    "NWS site",
+    "Pharmacy",
+    "Radios and devices",
+    "Unassigned",
+    "Wall cloud",
+    "Overlayed GPS symbol",
+    "Unassigned",
+    "Overlayed aircraft",
+    "Overlayed weather station",
+    "Rain",
+    "ARRL, ARES, Winlink",
+    "Blowing dust",
+    "Civil defense",
+    "DX spot",
+    "Sleet",
+    "Funnel cloud",
+    "Gale flags",
+    "Store",
+    "Point of interest",
+    "Work zone",
+    "Special vehicle",
+    "Area",
+    "Value sign",
+    "Overlayed triangle",
+    "Small circle",
+    "Partly cloudy",
+    "Unassigned",
+    "Restrooms",
+    "Overlayed ship",
+    "Tornado",
+    "Overlayed truck",
+    "Overlayed van",
+    "Flooding",
+    "Wreck or obstruction",
+    "Skywarn",
+    "Overlayed shelter",
+    "Fog",
+    "TNC stream switch",
+    "Unassigned",
+    "TNC stream switch",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new() {
+        assert_eq!(Symbol::new('/', '>'), Some(Symbol::CAR));
+        assert_eq!(Symbol::new('\\', '!'), Some(Symbol::EMERGENCY));
+        assert_eq!(
+            Symbol::new('c', '#'),
+            Some(Symbol::DIGI.with_overlay('2').unwrap())
+        );
+        assert_eq!(Symbol::new('x', '>'), None);
+        assert_eq!(Symbol::new('/', ' '), None);
+        assert_eq!(Symbol::CAR.with_overlay('a'), None);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Symbol::CAR.name(), "Car");
+        assert_eq!(Symbol::LARGE_AIRCRAFT.name(), "Large aircraft");
+        assert_eq!(Symbol::new('\\', '^').unwrap().name(), "Overlayed aircraft");
+        assert_eq!(Symbol::new('/', '~').unwrap().name(), "TNC stream switch");
+        assert_eq!(Symbol::new('\\', '~').unwrap().name(), "TNC stream switch");
+
+        let digi = Symbol::new('S', '#').unwrap();
+        assert_eq!(digi.overlay(), Some('S'));
+        assert!(!digi.is_primary());
+        assert_eq!(digi.description(), "Overlayed digi (overlay S)");
+        assert_eq!(Symbol::CAR.description(), "Car");
+    }
+
+    #[test]
+    fn to_destination() {
+        let to = |table, code| {
+            Symbol::new(table, code)
+                .unwrap()
+                .to_destination()
+                .to_string()
+        };
+
+        assert_eq!(to('/', '>'), "GPSMV");
+        assert_eq!(to('/', '!'), "GPSBB");
+        assert_eq!(to('/', '0'), "GPSP0");
+        assert_eq!(to('/', 'A'), "GPSPA");
+        assert_eq!(to('/', '_'), "GPSHW");
+        assert_eq!(to('/', 'k'), "GPSLK");
+        assert_eq!(to('/', '~'), "GPSJ4");
+        assert_eq!(to('\\', '!'), "GPSOB");
+        assert_eq!(to('\\', '9'), "GPSA9");
+        assert_eq!(to('S', '#'), "GPSODS");
+        assert_eq!(to('1', 'z'), "GPSSZ1");
+    }
+
+    #[test]
+    fn from_destination() {
+        let from = |call: &str| Symbol::from_destination(&Callsign::new(call, None));
+
+        assert_eq!(from("GPSMV"), Some(Symbol::CAR));
+        assert_eq!(from("SYMMV"), Some(Symbol::CAR));
+        assert_eq!(from("GPSMV "), Some(Symbol::CAR));
+        assert_eq!(from("GPSODS"), Symbol::new('S', '#'));
+        assert_eq!(from("GPSC30"), Some(Symbol::CAR));
+        assert_eq!(from("GPSE01"), Some(Symbol::EMERGENCY));
+        assert_eq!(from("GPSMVS"), None);
+        assert_eq!(from("GPSC95"), None);
+        assert_eq!(from("GPSP:"), None);
+        assert_eq!(from("APRS"), None);
+        assert_eq!(from("GPSXY"), None);
+    }
+
+    #[test]
+    fn destination_round_trip() {
+        for table in &['/', '\\', '0', 'Z'] {
+            for code in b'!'..=b'~' {
+                let symbol = Symbol::new(*table, code as char).unwrap();
+                assert_eq!(
+                    Symbol::from_destination(&symbol.to_destination()),
+                    Some(symbol)
+                );
+            }
+        }
+    }
+}
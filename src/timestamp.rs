@@ -1,42 +1,206 @@
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
 use bytes::parse_bytes;
-use std::convert::TryFrom;
-use std::io::Write;
+use core::convert::TryFrom;
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 
 use AprsError;
 use EncodeError;
+use EncodeWrite;
 
 #[derive(Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Timestamp {
     /// Day of month, Hour and Minute in UTC
     DDHHMM(u8, u8, u8),
     /// Hour, Minute and Second in UTC
     HHMMSS(u8, u8, u8),
-    /// Unsupported timestamp format
-    Unsupported(Vec<u8>),
+    /// Day of month, Hour and Minute in the sender's local time zone
+    DDHHMMLocal(u8, u8, u8),
+    /// Month, Day of month, Hour and Minute in UTC, used by positionless
+    /// weather reports
+    MDHM(u8, u8, u8, u8),
+    /// Unsupported timestamp format, kept verbatim. This includes the
+    /// non-standard uppercase `Z` and `H` markers.
+    Unsupported(#[cfg_attr(feature = "serde", serde(with = "::serde_support::bytes"))] Vec<u8>),
+}
+
+/// Field of a [`Timestamp`], see [`AprsError::TimestampOutOfRange`]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum TimestampField {
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
 }
 
 impl Timestamp {
-    pub fn encode<W: Write>(&self, buf: &mut W) -> Result<(), EncodeError> {
+    /// Checked constructor for [`Timestamp::DDHHMM`]
+    pub fn new_ddhhmm(day: u8, hour: u8, minute: u8) -> Result<Self, AprsError> {
+        let timestamp = Self::DDHHMM(day, hour, minute);
+        timestamp.validate()?;
+        Ok(timestamp)
+    }
+
+    /// Checked constructor for [`Timestamp::HHMMSS`]
+    pub fn new_hhmmss(hour: u8, minute: u8, second: u8) -> Result<Self, AprsError> {
+        let timestamp = Self::HHMMSS(hour, minute, second);
+        timestamp.validate()?;
+        Ok(timestamp)
+    }
+
+    /// Checked constructor for [`Timestamp::DDHHMMLocal`]
+    pub fn new_ddhhmm_local(day: u8, hour: u8, minute: u8) -> Result<Self, AprsError> {
+        let timestamp = Self::DDHHMMLocal(day, hour, minute);
+        timestamp.validate()?;
+        Ok(timestamp)
+    }
+
+    /// Checked constructor for [`Timestamp::MDHM`]
+    pub fn new_mdhm(month: u8, day: u8, hour: u8, minute: u8) -> Result<Self, AprsError> {
+        let timestamp = Self::MDHM(month, day, hour, minute);
+        timestamp.validate()?;
+        Ok(timestamp)
+    }
+
+    /// Parses a timestamp and rejects out-of-range fields.
+    ///
+    /// `Timestamp::try_from` is lenient and accepts e.g. `993499z`, which
+    /// shows up in real-world feeds.
+    pub fn parse_strict(b: &[u8]) -> Result<Self, AprsError> {
+        let timestamp = Self::try_from(b)?;
+        timestamp.validate()?;
+        Ok(timestamp)
+    }
+
+    /// Checks that days are within 1-31, months within 1-12, hours within
+    /// 0-23 and minutes and seconds within 0-59. Unsupported timestamps are
+    /// always valid.
+    pub fn validate(&self) -> Result<(), AprsError> {
+        use self::TimestampField::*;
+
+        match *self {
+            Self::DDHHMM(d, h, m) | Self::DDHHMMLocal(d, h, m) => {
+                check_range(Day, d, 1, 31)?;
+                check_range(Hour, h, 0, 23)?;
+                check_range(Minute, m, 0, 59)
+            }
+            Self::HHMMSS(h, m, s) => {
+                check_range(Hour, h, 0, 23)?;
+                check_range(Minute, m, 0, 59)?;
+                check_range(Second, s, 0, 59)
+            }
+            Self::MDHM(mo, d, h, m) => {
+                check_range(Month, mo, 1, 12)?;
+                check_range(Day, d, 1, 31)?;
+                check_range(Hour, h, 0, 23)?;
+                check_range(Minute, m, 0, 59)
+            }
+            Self::Unsupported(_) => Ok(()),
+        }
+    }
+
+    pub fn encode<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
         match self {
             Self::DDHHMM(d, h, m) => write!(buf, "{:02}{:02}{:02}z", d, h, m)?,
             Self::HHMMSS(h, m, s) => write!(buf, "{:02}{:02}{:02}h", h, m, s)?,
+            Self::DDHHMMLocal(d, h, m) => write!(buf, "{:02}{:02}{:02}/", d, h, m)?,
+            Self::MDHM(mo, d, h, m) => write!(buf, "{:02}{:02}{:02}{:02}", mo, d, h, m)?,
             Self::Unsupported(s) => buf.write_all(s)?,
         };
 
         Ok(())
     }
+
+    /// Resolves the timestamp to an absolute UTC date-time.
+    ///
+    /// The missing date fields are taken from `reference`, usually the time
+    /// the packet was received. Of the candidates within two months
+    /// (`DDHHMM`) or one day (`HHMMSS`) of `reference` the closest one is
+    /// returned, so `312359z` received on January 1st resolves to December
+    /// 31st of the previous year.
+    ///
+    /// `MDHM` is resolved the same way using the previous, current and next
+    /// year.
+    ///
+    /// Returns `None` for local time, unsupported timestamps and out-of-range
+    /// fields.
+    #[cfg(feature = "chrono")]
+    pub fn to_datetime(&self, reference: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let reference = reference.naive_utc();
+        let date = reference.date();
+
+        match *self {
+            Self::DDHHMM(d, h, m) => {
+                let time = NaiveTime::from_hms_opt(h.into(), m.into(), 0)?;
+                // two months either way, so a 31st always has a candidate
+                let dates = (-2..=2).map(|months| add_months(date, months, d));
+                closest(dates, time, reference)
+            }
+            Self::HHMMSS(h, m, s) => {
+                let time = NaiveTime::from_hms_opt(h.into(), m.into(), s.into())?;
+                let dates = [date.pred_opt(), Some(date), date.succ_opt()];
+                closest(dates.iter().copied(), time, reference)
+            }
+            Self::MDHM(mo, d, h, m) => {
+                let time = NaiveTime::from_hms_opt(h.into(), m.into(), 0)?;
+                let dates = (-1..=1)
+                    .map(|years| NaiveDate::from_ymd_opt(date.year() + years, mo.into(), d.into()));
+                closest(dates, time, reference)
+            }
+            Self::DDHHMMLocal(..) | Self::Unsupported(_) => None,
+        }
+    }
+}
+
+/// The candidate date at `time` closest to `reference`
+#[cfg(feature = "chrono")]
+fn closest<I: Iterator<Item = Option<NaiveDate>>>(
+    dates: I,
+    time: NaiveTime,
+    reference: NaiveDateTime,
+) -> Option<DateTime<Utc>> {
+    dates
+        .flatten()
+        .map(|date| date.and_time(time))
+        .min_by_key(|candidate| {
+            candidate
+                .signed_duration_since(reference)
+                .num_seconds()
+                .abs()
+        })
+        .map(|candidate| Utc.from_utc_datetime(&candidate))
+}
+
+/// Day `day` of the month `months` after the month of `date`
+#[cfg(feature = "chrono")]
+fn add_months(date: NaiveDate, months: i32, day: u8) -> Option<NaiveDate> {
+    let month = date.year() * 12 + date.month0() as i32 + months;
+    NaiveDate::from_ymd_opt(
+        month.div_euclid(12),
+        month.rem_euclid(12) as u32 + 1,
+        day.into(),
+    )
 }
 
 impl TryFrom<&[u8]> for Timestamp {
     type Error = AprsError;
 
     fn try_from(b: &[u8]) -> Result<Self, Self::Error> {
+        if b.len() == 8 {
+            return parse_mdhm(b).ok_or_else(|| AprsError::InvalidTimestamp(b.to_owned()));
+        }
+
         if b.len() != 7 {
             return Err(AprsError::InvalidTimestamp(b.to_owned()));
         }
 
         if b[6] == b'/' {
-            return Ok(Timestamp::Unsupported(b.to_owned()));
+            // local time is rare and often garbage, keep it as it is
+            return Ok(parse_local(b).unwrap_or_else(|| Timestamp::Unsupported(b.to_owned())));
         }
 
         let one = parse_bytes(&b[0..2]).ok_or_else(|| AprsError::InvalidTimestamp(b.to_owned()))?;
@@ -45,13 +209,40 @@ impl TryFrom<&[u8]> for Timestamp {
             parse_bytes(&b[4..6]).ok_or_else(|| AprsError::InvalidTimestamp(b.to_owned()))?;
 
         Ok(match b[6] {
-            b'z' | b'Z' => Timestamp::DDHHMM(one, two, three),
-            b'h' | b'H' => Timestamp::HHMMSS(one, two, three),
+            b'z' => Timestamp::DDHHMM(one, two, three),
+            b'h' => Timestamp::HHMMSS(one, two, three),
+            // not in the spec, keep the marker so the packet re-encodes as it was
+            b'Z' | b'H' => Timestamp::Unsupported(b.to_owned()),
             _ => return Err(AprsError::InvalidTimestamp(b.to_owned())),
         })
     }
 }
 
+fn parse_local(b: &[u8]) -> Option<Timestamp> {
+    let day = parse_bytes(&b[0..2])?;
+    let hour = parse_bytes(&b[2..4])?;
+    let minute = parse_bytes(&b[4..6])?;
+
+    Timestamp::new_ddhhmm_local(day, hour, minute).ok()
+}
+
+fn parse_mdhm(b: &[u8]) -> Option<Timestamp> {
+    let month = parse_bytes(&b[0..2])?;
+    let day = parse_bytes(&b[2..4])?;
+    let hour = parse_bytes(&b[4..6])?;
+    let minute = parse_bytes(&b[6..8])?;
+
+    Timestamp::new_mdhm(month, day, hour, minute).ok()
+}
+
+fn check_range(field: TimestampField, value: u8, min: u8, max: u8) -> Result<(), AprsError> {
+    if value < min || value > max {
+        return Err(AprsError::TimestampOutOfRange { field, value });
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,7 +256,7 @@ mod tests {
 
         assert_eq!(
             Timestamp::try_from(&b"123456Z"[..]),
-            Ok(Timestamp::DDHHMM(12, 34, 56))
+            Ok(Timestamp::Unsupported(b"123456Z".to_vec()))
         );
     }
 
@@ -78,18 +269,59 @@ mod tests {
 
         assert_eq!(
             Timestamp::try_from(&b"123456H"[..]),
-            Ok(Timestamp::HHMMSS(12, 34, 56))
+            Ok(Timestamp::Unsupported(b"123456H".to_vec()))
         );
     }
 
     #[test]
     fn parse_local_time() {
         assert_eq!(
-            Timestamp::try_from(&b"123456/"[..]),
-            Ok(Timestamp::Unsupported(b"123456/".to_vec()))
+            Timestamp::try_from(&b"122356/"[..]),
+            Ok(Timestamp::DDHHMMLocal(12, 23, 56))
+        );
+    }
+
+    #[test]
+    fn parse_mdhm() {
+        assert_eq!(
+            Timestamp::try_from(&b"10090556"[..]),
+            Ok(Timestamp::MDHM(10, 9, 5, 56))
         );
     }
 
+    #[test]
+    fn unsupported_local_time() {
+        for b in &[
+            &b"002356/"[..],
+            b"322356/",
+            b"122456/",
+            b"122360/",
+            b"1223a6/",
+        ] {
+            assert_eq!(
+                Timestamp::try_from(*b),
+                Ok(Timestamp::Unsupported(b.to_vec()))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_mdhm() {
+        for b in &[
+            &b"00090556"[..],
+            b"13090556",
+            b"10320556",
+            b"10092456",
+            b"10090560",
+            b"1009055a",
+        ] {
+            assert_eq!(
+                Timestamp::try_from(*b),
+                Err(AprsError::InvalidTimestamp(b.to_vec()))
+            );
+        }
+    }
+
     #[test]
     fn invalid_timestamp() {
         assert_eq!(
@@ -106,6 +338,176 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "chrono")]
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(
+            &NaiveDate::from_ymd_opt(y, mo, d)
+                .unwrap()
+                .and_hms_opt(h, mi, s)
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn ddhhmm_to_datetime() {
+        let reference = utc(2026, 10, 18, 12, 0, 0);
+        assert_eq!(
+            Timestamp::DDHHMM(18, 11, 55).to_datetime(&reference),
+            Some(utc(2026, 10, 18, 11, 55, 0))
+        );
+
+        // month and year rollover
+        assert_eq!(
+            Timestamp::DDHHMM(30, 23, 50).to_datetime(&utc(2026, 10, 1, 0, 10, 0)),
+            Some(utc(2026, 9, 30, 23, 50, 0))
+        );
+        assert_eq!(
+            Timestamp::DDHHMM(31, 23, 59).to_datetime(&utc(2027, 1, 1, 0, 1, 0)),
+            Some(utc(2026, 12, 31, 23, 59, 0))
+        );
+        assert_eq!(
+            Timestamp::DDHHMM(1, 0, 1).to_datetime(&utc(2026, 12, 31, 23, 59, 0)),
+            Some(utc(2027, 1, 1, 0, 1, 0))
+        );
+
+        // February has no 31st, January 31st is 28.5 days back and closer
+        // than March 31st
+        assert_eq!(
+            Timestamp::DDHHMM(31, 12, 0).to_datetime(&utc(2026, 3, 1, 0, 0, 0)),
+            Some(utc(2026, 1, 31, 12, 0, 0))
+        );
+        assert_eq!(
+            Timestamp::DDHHMM(31, 12, 0).to_datetime(&utc(2026, 3, 3, 0, 0, 0)),
+            Some(utc(2026, 3, 31, 12, 0, 0))
+        );
+
+        assert_eq!(Timestamp::DDHHMM(32, 0, 0).to_datetime(&reference), None);
+        assert_eq!(Timestamp::DDHHMM(1, 24, 0).to_datetime(&reference), None);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn hhmmss_to_datetime() {
+        assert_eq!(
+            Timestamp::HHMMSS(7, 48, 49).to_datetime(&utc(2026, 10, 18, 7, 49, 0)),
+            Some(utc(2026, 10, 18, 7, 48, 49))
+        );
+        assert_eq!(
+            Timestamp::HHMMSS(23, 59, 50).to_datetime(&utc(2026, 1, 1, 0, 0, 5)),
+            Some(utc(2025, 12, 31, 23, 59, 50))
+        );
+        assert_eq!(
+            Timestamp::HHMMSS(0, 0, 5).to_datetime(&utc(2026, 2, 28, 23, 59, 50)),
+            Some(utc(2026, 3, 1, 0, 0, 5))
+        );
+        assert_eq!(
+            Timestamp::HHMMSS(12, 60, 0).to_datetime(&utc(2026, 2, 28, 23, 59, 50)),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn mdhm_to_datetime() {
+        assert_eq!(
+            Timestamp::MDHM(10, 18, 11, 55).to_datetime(&utc(2026, 10, 18, 12, 0, 0)),
+            Some(utc(2026, 10, 18, 11, 55, 0))
+        );
+        assert_eq!(
+            Timestamp::MDHM(12, 31, 23, 59).to_datetime(&utc(2027, 1, 1, 0, 1, 0)),
+            Some(utc(2026, 12, 31, 23, 59, 0))
+        );
+        assert_eq!(
+            Timestamp::MDHM(2, 30, 0, 0).to_datetime(&utc(2027, 1, 1, 0, 1, 0)),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn unsupported_to_datetime() {
+        assert_eq!(
+            Timestamp::Unsupported(b"123456/".to_vec()).to_datetime(&utc(2026, 10, 18, 0, 0, 0)),
+            None
+        );
+        assert_eq!(
+            Timestamp::DDHHMMLocal(18, 0, 0).to_datetime(&utc(2026, 10, 18, 0, 0, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_strict() {
+        assert_eq!(
+            Timestamp::try_from(&b"993499z"[..]),
+            Ok(Timestamp::DDHHMM(99, 34, 99))
+        );
+        assert_eq!(
+            Timestamp::parse_strict(&b"993499z"[..]),
+            Err(AprsError::TimestampOutOfRange {
+                field: TimestampField::Day,
+                value: 99
+            })
+        );
+        assert_eq!(
+            Timestamp::parse_strict(&b"123460h"[..]),
+            Err(AprsError::TimestampOutOfRange {
+                field: TimestampField::Second,
+                value: 60
+            })
+        );
+        assert_eq!(
+            Timestamp::parse_strict(&b"312359z"[..]),
+            Ok(Timestamp::DDHHMM(31, 23, 59))
+        );
+        assert_eq!(
+            Timestamp::parse_strict(&b"123a56z"[..]),
+            Err(AprsError::InvalidTimestamp(b"123a56z".to_vec()))
+        );
+    }
+
+    #[test]
+    fn checked_constructors() {
+        assert_eq!(
+            Timestamp::new_ddhhmm(1, 0, 0),
+            Ok(Timestamp::DDHHMM(1, 0, 0))
+        );
+        assert_eq!(
+            Timestamp::new_ddhhmm(0, 0, 0),
+            Err(AprsError::TimestampOutOfRange {
+                field: TimestampField::Day,
+                value: 0
+            })
+        );
+        assert_eq!(
+            Timestamp::new_hhmmss(23, 59, 59),
+            Ok(Timestamp::HHMMSS(23, 59, 59))
+        );
+        assert_eq!(
+            Timestamp::new_hhmmss(65, 43, 21),
+            Err(AprsError::TimestampOutOfRange {
+                field: TimestampField::Hour,
+                value: 65
+            })
+        );
+        assert_eq!(
+            Timestamp::new_ddhhmm_local(12, 0, 60),
+            Err(AprsError::TimestampOutOfRange {
+                field: TimestampField::Minute,
+                value: 60
+            })
+        );
+        assert_eq!(
+            Timestamp::new_mdhm(13, 1, 0, 0),
+            Err(AprsError::TimestampOutOfRange {
+                field: TimestampField::Month,
+                value: 13
+            })
+        );
+        assert!(Timestamp::Unsupported(b"abc".to_vec()).validate().is_ok());
+    }
+
     #[test]
     fn encode_ddhhmm() {
         let mut buf = vec![];
@@ -120,6 +522,29 @@ mod tests {
         assert_eq!(b"654321h"[..], buf);
     }
 
+    #[test]
+    fn encode_ddhhmm_local() {
+        let mut buf = vec![];
+        Timestamp::DDHHMMLocal(12, 23, 56).encode(&mut buf).unwrap();
+        assert_eq!(b"122356/"[..], buf);
+    }
+
+    #[test]
+    fn encode_mdhm() {
+        let mut buf = vec![];
+        Timestamp::MDHM(10, 9, 5, 56).encode(&mut buf).unwrap();
+        assert_eq!(b"10090556"[..], buf);
+    }
+
+    #[test]
+    fn round_trip_local_time_and_mdhm() {
+        for b in &[&b"311159/"[..], b"01010000", b"12312359"] {
+            let mut buf = vec![];
+            Timestamp::try_from(*b).unwrap().encode(&mut buf).unwrap();
+            assert_eq!(*b, &buf[..]);
+        }
+    }
+
     #[test]
     fn encode_local_time() {
         let mut buf = vec![];
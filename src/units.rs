@@ -0,0 +1,235 @@
+// physical quantities
+//
+// APRS mixes units freely: knots and statute miles in compressed positions,
+// feet for altitudes, and OGN comments add feet per minute for climb rates
+// and standard rate turns for the rate of turn.
+// These types store SI values and convert on access, so callers never have
+// to remember which unit a field came in.
+
+/// Speed, stored in meters per second
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Speed(f64);
+
+impl Speed {
+    pub fn from_meters_per_second(value: f64) -> Self {
+        Self(value)
+    }
+
+    pub fn from_kilometers_per_hour(value: f64) -> Self {
+        Self(value / 3.6)
+    }
+
+    pub fn from_knots(value: f64) -> Self {
+        Self(value * METERS_PER_NAUTICAL_MILE / 3600.0)
+    }
+
+    pub fn from_miles_per_hour(value: f64) -> Self {
+        Self(value * METERS_PER_STATUTE_MILE / 3600.0)
+    }
+
+    pub fn meters_per_second(self) -> f64 {
+        self.0
+    }
+
+    pub fn kilometers_per_hour(self) -> f64 {
+        self.0 * 3.6
+    }
+
+    pub fn knots(self) -> f64 {
+        self.0 * 3600.0 / METERS_PER_NAUTICAL_MILE
+    }
+
+    pub fn miles_per_hour(self) -> f64 {
+        self.0 * 3600.0 / METERS_PER_STATUTE_MILE
+    }
+}
+
+/// Length, distance or altitude, stored in meters
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Length(f64);
+
+impl Length {
+    pub fn from_meters(value: f64) -> Self {
+        Self(value)
+    }
+
+    pub fn from_kilometers(value: f64) -> Self {
+        Self(value * 1000.0)
+    }
+
+    pub fn from_feet(value: f64) -> Self {
+        Self(value * METERS_PER_FOOT)
+    }
+
+    pub fn from_statute_miles(value: f64) -> Self {
+        Self(value * METERS_PER_STATUTE_MILE)
+    }
+
+    pub fn from_nautical_miles(value: f64) -> Self {
+        Self(value * METERS_PER_NAUTICAL_MILE)
+    }
+
+    pub fn meters(self) -> f64 {
+        self.0
+    }
+
+    pub fn kilometers(self) -> f64 {
+        self.0 / 1000.0
+    }
+
+    pub fn feet(self) -> f64 {
+        self.0 / METERS_PER_FOOT
+    }
+
+    pub fn statute_miles(self) -> f64 {
+        self.0 / METERS_PER_STATUTE_MILE
+    }
+
+    pub fn nautical_miles(self) -> f64 {
+        self.0 / METERS_PER_NAUTICAL_MILE
+    }
+}
+
+/// Angle, e.g. a course or bearing, stored in degrees
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Angle(f64);
+
+impl Angle {
+    pub fn from_degrees(value: f64) -> Self {
+        Self(value)
+    }
+
+    pub fn from_radians(value: f64) -> Self {
+        Self(value * (180.0 / core::f64::consts::PI))
+    }
+
+    pub fn degrees(self) -> f64 {
+        self.0
+    }
+
+    pub fn radians(self) -> f64 {
+        self.0 * (core::f64::consts::PI / 180.0)
+    }
+}
+
+/// Climb (positive) or sink rate, stored in meters per second
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VerticalSpeed(f64);
+
+impl VerticalSpeed {
+    pub fn from_meters_per_second(value: f64) -> Self {
+        Self(value)
+    }
+
+    pub fn from_feet_per_minute(value: f64) -> Self {
+        Self(value * METERS_PER_FOOT / 60.0)
+    }
+
+    pub fn meters_per_second(self) -> f64 {
+        self.0
+    }
+
+    pub fn feet_per_minute(self) -> f64 {
+        self.0 * 60.0 / METERS_PER_FOOT
+    }
+}
+
+/// Rate of turn, stored in degrees per second. Positive values turn
+/// clockwise.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TurnRate(f64);
+
+impl TurnRate {
+    pub fn from_degrees_per_second(value: f64) -> Self {
+        Self(value)
+    }
+
+    /// OGN `rot`, 1.0 is a standard rate turn of 3°/s
+    pub fn from_half_turns_per_minute(value: f64) -> Self {
+        Self(value * 3.0)
+    }
+
+    pub fn degrees_per_second(self) -> f64 {
+        self.0
+    }
+
+    pub fn radians_per_second(self) -> f64 {
+        self.0 * (core::f64::consts::PI / 180.0)
+    }
+
+    pub fn half_turns_per_minute(self) -> f64 {
+        self.0 / 3.0
+    }
+}
+
+const METERS_PER_FOOT: f64 = 0.3048;
+const METERS_PER_STATUTE_MILE: f64 = 1609.344;
+const METERS_PER_NAUTICAL_MILE: f64 = 1852.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed() {
+        let speed = Speed::from_knots(10.0);
+        assert_relative_eq!(speed.meters_per_second(), 5.144_444, epsilon = 1e-6);
+        assert_relative_eq!(speed.kilometers_per_hour(), 18.52);
+        assert_relative_eq!(speed.miles_per_hour(), 11.507_794, epsilon = 1e-6);
+        assert_relative_eq!(speed.knots(), 10.0);
+
+        assert_relative_eq!(
+            Speed::from_kilometers_per_hour(36.0).meters_per_second(),
+            10.0
+        );
+        assert_relative_eq!(
+            Speed::from_miles_per_hour(60.0).kilometers_per_hour(),
+            96.56064
+        );
+    }
+
+    #[test]
+    fn length() {
+        let length = Length::from_feet(3054.0);
+        assert_relative_eq!(length.meters(), 930.8592);
+        assert_relative_eq!(length.feet(), 3054.0);
+
+        assert_relative_eq!(Length::from_statute_miles(1.0).kilometers(), 1.609_344);
+        assert_relative_eq!(
+            Length::from_nautical_miles(1.0).statute_miles(),
+            1.150_779,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(Length::from_kilometers(1.852).nautical_miles(), 1.0);
+    }
+
+    #[test]
+    fn angle() {
+        assert_relative_eq!(Angle::from_degrees(180.0).radians(), core::f64::consts::PI);
+        assert_relative_eq!(
+            Angle::from_radians(core::f64::consts::FRAC_PI_2).degrees(),
+            90.0
+        );
+    }
+
+    #[test]
+    fn vertical_speed() {
+        let climb = VerticalSpeed::from_feet_per_minute(-39.0);
+        assert_relative_eq!(climb.meters_per_second(), -0.198_12, epsilon = 1e-6);
+        assert_relative_eq!(climb.feet_per_minute(), -39.0);
+        assert!(climb < VerticalSpeed::default());
+    }
+
+    #[test]
+    fn turn_rate() {
+        let rate = TurnRate::from_half_turns_per_minute(-0.5);
+        assert_relative_eq!(rate.degrees_per_second(), -1.5);
+        assert_relative_eq!(rate.radians_per_second(), -0.026_179_938, epsilon = 1e-9);
+        assert_relative_eq!(rate.half_turns_per_minute(), -0.5);
+    }
+}
@@ -0,0 +1,219 @@
+// user-defined and invalid/test data
+//
+// User-defined data starts with `{`, followed by a user ID and a packet type
+// character chosen by the author of the format, e.g. `{Q1...`. The user ID `{`
+// is reserved for experimental formats. Invalid data and test data start with
+// `,` and have no further structure.
+
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use AprsData;
+use AprsError;
+use AprsPacket;
+use EncodeError;
+use EncodeWrite;
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AprsUserDefined {
+    pub user_id: char,
+    pub packet_type: char,
+    #[cfg_attr(feature = "serde", serde(with = "::serde_support::bytes"))]
+    pub data: Vec<u8>,
+}
+
+impl AprsUserDefined {
+    /// `{{` packets
+    pub fn is_experimental(&self) -> bool {
+        self.user_id == '{'
+    }
+
+    pub fn encode<W: EncodeWrite>(&self, buf: &mut W) -> Result<(), EncodeError> {
+        write!(buf, "{{{}{}", self.user_id, self.packet_type)?;
+        buf.write_all(&self.data)?;
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for AprsUserDefined {
+    type Error = AprsError;
+
+    /// Parses the data after the leading `{`
+    fn try_from(b: &[u8]) -> Result<Self, AprsError> {
+        AprsUserDefinedRef::try_from(b).map(AprsUserDefinedRef::into_owned)
+    }
+}
+
+/// Borrowed version of [`AprsUserDefined`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AprsUserDefinedRef<'a> {
+    pub user_id: char,
+    pub packet_type: char,
+    pub data: &'a [u8],
+}
+
+impl<'a> AprsUserDefinedRef<'a> {
+    pub fn into_owned(self) -> AprsUserDefined {
+        AprsUserDefined {
+            user_id: self.user_id,
+            packet_type: self.packet_type,
+            data: self.data.to_vec(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for AprsUserDefinedRef<'a> {
+    type Error = AprsError;
+
+    fn try_from(b: &'a [u8]) -> Result<Self, AprsError> {
+        match b {
+            [user_id, packet_type, data @ ..]
+                if user_id.is_ascii_graphic() && packet_type.is_ascii_graphic() =>
+            {
+                Ok(Self {
+                    user_id: *user_id as char,
+                    packet_type: *packet_type as char,
+                    data,
+                })
+            }
+            _ => Err(AprsError::InvalidUserDefined(b.to_owned())),
+        }
+    }
+}
+
+type Decoder<T> = Box<dyn Fn(char, &[u8]) -> Option<T>>;
+
+/// Application provided decoders for user-defined formats
+///
+/// ```rust
+/// # extern crate aprs_parser;
+/// # use std::convert::TryFrom;
+/// # use aprs_parser::{AprsPacket, UserDefinedDecoders};
+/// let mut decoders = UserDefinedDecoders::new();
+/// decoders.register('Q', |packet_type, data| match packet_type {
+///     'T' => std::str::from_utf8(data).ok()?.parse::<f64>().ok(),
+///     _ => None,
+/// });
+///
+/// let packet = AprsPacket::try_from(&b"N0CALL>APRS:{QT21.5"[..]).unwrap();
+/// assert_eq!(decoders.decode_packet(&packet), Some(21.5));
+/// ```
+pub struct UserDefinedDecoders<T> {
+    decoders: BTreeMap<char, Decoder<T>>,
+}
+
+impl<T> UserDefinedDecoders<T> {
+    pub fn new() -> Self {
+        Self {
+            decoders: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the decoder for a user ID, replacing any previous one.
+    /// The decoder gets the packet type and the data after it.
+    pub fn register<F>(&mut self, user_id: char, decoder: F)
+    where
+        F: Fn(char, &[u8]) -> Option<T> + 'static,
+    {
+        self.decoders.insert(user_id, Box::new(decoder));
+    }
+
+    /// `None` if no decoder is registered for the user ID or the decoder fails
+    pub fn decode(&self, user_defined: &AprsUserDefined) -> Option<T> {
+        let decoder = self.decoders.get(&user_defined.user_id)?;
+        decoder(user_defined.packet_type, &user_defined.data)
+    }
+
+    /// `None` for packets without user-defined data
+    pub fn decode_packet(&self, packet: &AprsPacket) -> Option<T> {
+        match &packet.data {
+            AprsData::UserDefined(user_defined) => self.decode(user_defined),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Default for UserDefinedDecoders<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            AprsUserDefined::try_from(&b"Q1abc"[..]),
+            Ok(AprsUserDefined {
+                user_id: 'Q',
+                packet_type: '1',
+                data: b"abc".to_vec(),
+            })
+        );
+
+        let experimental = AprsUserDefined::try_from(&b"{X"[..]).unwrap();
+        assert!(experimental.is_experimental());
+        assert_eq!(experimental.data, b"");
+
+        assert_eq!(
+            AprsUserDefined::try_from(&b"Q"[..]),
+            Err(AprsError::InvalidUserDefined(b"Q".to_vec()))
+        );
+        assert_eq!(
+            AprsUserDefined::try_from(&b"Q abc"[..]),
+            Err(AprsError::InvalidUserDefined(b"Q abc".to_vec()))
+        );
+    }
+
+    #[test]
+    fn encode() {
+        let mut buf = vec![];
+        AprsUserDefined {
+            user_id: 'Q',
+            packet_type: '1',
+            data: b"\xff\x00abc".to_vec(),
+        }
+        .encode(&mut buf)
+        .unwrap();
+        assert_eq!(buf, b"{Q1\xff\x00abc");
+    }
+
+    #[test]
+    fn decoders() {
+        #[derive(Debug, PartialEq)]
+        enum Custom {
+            Temperature(u8),
+            Text(Vec<u8>),
+        }
+
+        let mut decoders = UserDefinedDecoders::new();
+        decoders.register('Q', |packet_type, data| match packet_type {
+            'T' => Some(Custom::Temperature(*data.first()?)),
+            _ => None,
+        });
+        decoders.register('{', |_, data| Some(Custom::Text(data.to_vec())));
+
+        let decode = |b: &[u8]| decoders.decode_packet(&AprsPacket::try_from(b).unwrap());
+
+        assert_eq!(
+            decode(b"N0CALL>APRS:{QT\x15"),
+            Some(Custom::Temperature(21))
+        );
+        assert_eq!(decode(b"N0CALL>APRS:{QT"), None);
+        assert_eq!(decode(b"N0CALL>APRS:{QX\x15"), None);
+        assert_eq!(
+            decode(b"N0CALL>APRS:{{Xtest"),
+            Some(Custom::Text(b"test".to_vec()))
+        );
+        assert_eq!(decode(b"N0CALL>APRS:{ZT\x15"), None);
+        assert_eq!(decode(b"N0CALL>APRS:,test"), None);
+    }
+}
@@ -0,0 +1,49 @@
+//! `encode_to_slice` and `encoded_len` must not allocate. The counting
+//! allocator is global to this test binary, so it holds a single test.
+
+extern crate aprs_parser;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use aprs_parser::AprsPacket;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn encode_without_allocating() {
+    let packets: Vec<AprsPacket> = [
+        &br"ICA3D17F2>APRS,qAS,dl4mea:/074849h4821.61N\01224.49E^322/103/A=003054"[..],
+        &br"ICA3D17F2>APRS,qAS,dl4mea:@074849h/ABCD#$%^^{?C322/103/A=003054"[..],
+        &b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :Hello World! {3a2B975"[..],
+        &b"N0CALL>APRS,WIDE1-1:{Q1user defined"[..],
+        &b"N0CALL>APRS,WIDE1-1:>status"[..],
+    ]
+    .iter()
+    .map(|b| aprs_parser::parse(b).unwrap())
+    .collect();
+    let mut buf = [0; 256];
+
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    for packet in &packets {
+        let len = packet.encode_to_slice(&mut buf).unwrap();
+        assert_eq!(packet.encoded_len().unwrap(), len);
+    }
+    assert_eq!(ALLOCATIONS.load(Ordering::SeqCst), before);
+}
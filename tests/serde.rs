@@ -0,0 +1,126 @@
+// lives outside of the crate, serde_json's `PartialEq` impls break type
+// inference in the unit tests
+#![cfg(feature = "serde")]
+
+extern crate aprs_parser;
+extern crate serde_json;
+
+use std::convert::TryFrom;
+
+use aprs_parser::{AprsData, AprsPacket, Callsign, Latitude, Longitude, Precision};
+
+// coordinates are deserialized as exact values
+fn exact(mut packet: AprsPacket) -> AprsPacket {
+    if let AprsData::Position(position) = &mut packet.data {
+        position.latitude = Latitude::new(*position.latitude).unwrap();
+        position.longitude = Longitude::new(*position.longitude).unwrap();
+    }
+    packet
+}
+
+#[test]
+fn json_shape() {
+    let packet = AprsPacket::try_from(
+        &br"ICA3D17F2>APRS,qAS,dl4mea:/074849h4821.61N\01224.49E^322/103/A=003054"[..],
+    )
+    .unwrap();
+
+    let comment = if cfg!(feature = "serde-base64") {
+        "MzIyLzEwMy9BPTAwMzA1NA=="
+    } else {
+        "322/103/A=003054"
+    };
+    let json = serde_json::json!({
+        "from": "ICA3D17F2",
+        "to": "APRS",
+        "via": ["qAS", "dl4mea"],
+        "data": {
+            "Position": {
+                "timestamp": { "HHMMSS": [7, 48, 49] },
+                "messaging_supported": false,
+                "latitude": 48.36016666666667,
+                "longitude": 12.408166666666666,
+                "symbol_table": "\\",
+                "symbol_code": "^",
+                "comment": comment,
+                "cst": "Uncompressed"
+            }
+        }
+    });
+
+    assert_eq!(serde_json::to_value(&packet).unwrap(), json);
+    assert_eq!(
+        serde_json::from_value::<AprsPacket>(json).unwrap(),
+        exact(packet)
+    );
+}
+
+#[test]
+fn json_round_trip() {
+    let packets = [
+        &b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :Hello World! {3a2B975"[..],
+        &b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :No ID"[..],
+        &b"N0CALL>APRS:!/ABCD#$%^-X>DHello/A=001000"[..],
+        &b"N0CALL>APRS:=/ABCD#$%^-S]1"[..],
+        &b"N0CALL>APRS:@092345/4903.50N/07201.75W>"[..],
+    ];
+
+    for p in &packets {
+        let packet = AprsPacket::try_from(*p).unwrap();
+        let json = serde_json::to_string(&packet).unwrap();
+        let deserialized = serde_json::from_str::<AprsPacket>(&json).unwrap();
+        assert_eq!(deserialized, exact(packet));
+
+        // without ambiguity the coordinates still re-encode as they were
+        let mut buf = vec![];
+        deserialized.encode(&mut buf).unwrap();
+        assert_eq!(buf, *p);
+    }
+}
+
+#[test]
+fn coordinates_lose_precision() {
+    let packet = AprsPacket::try_from(&b"N0CALL>APRS:!4903.  N/07201.  W-"[..]).unwrap();
+    let json = serde_json::to_string(&packet).unwrap();
+    let deserialized = serde_json::from_str::<AprsPacket>(&json).unwrap();
+
+    assert_ne!(deserialized, packet);
+    assert_eq!(deserialized, exact(packet));
+
+    let position = match &deserialized.data {
+        AprsData::Position(position) => position,
+        _ => panic!("Unexpected data type"),
+    };
+    assert_eq!(position.latitude.precision(), Precision::Exact);
+    assert_eq!(position.longitude.precision(), Precision::Exact);
+
+    let mut buf = vec![];
+    deserialized.encode(&mut buf).unwrap();
+    assert_eq!(buf, &b"N0CALL>APRS:!4903.50N/07201.50W-"[..]);
+}
+
+#[test]
+fn non_utf8_bytes() {
+    let packet =
+        AprsPacket::try_from(&b"ICA3D17F2>Aprs,qAS,dl4mea::DEST     :\xc3\x28"[..]).unwrap();
+    let json = serde_json::to_value(&packet).unwrap();
+    let text = &json["data"]["Message"]["text"];
+
+    if cfg!(feature = "serde-base64") {
+        assert_eq!(text, "wyg=");
+        assert_eq!(serde_json::from_value::<AprsPacket>(json).unwrap(), packet);
+    } else {
+        assert_eq!(text, "\u{fffd}(");
+    }
+}
+
+#[test]
+fn invalid_values() {
+    assert!(serde_json::from_str::<Latitude>("91.0").is_err());
+    assert!(serde_json::from_str::<Longitude>("-181.0").is_err());
+    assert!(serde_json::from_str::<Callsign>("\"-1\"").is_err());
+    assert_eq!(
+        serde_json::from_str::<Callsign>("\"N0CALL-7\"").unwrap(),
+        Callsign::new("N0CALL", Some("7"))
+    );
+}